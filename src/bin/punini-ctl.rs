@@ -0,0 +1,68 @@
+// punini-ctl: kirim satu perintah ke punini yang sedang berjalan lewat Unix socket
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process;
+
+// Path socket yang sama dengan punini
+#[allow(dead_code)]
+#[path = "../dirs.rs"]
+mod dirs;
+
+const USAGE: &str = "usage: punini-ctl [--socket <path>] <pause|next|prev|shuffle|repeat|quit|seek <secs>|volume <0.0-1.5>>";
+
+fn main() {
+    let mut args = std::env::args().skip(1).peekable();
+    let mut socket = None;
+    if args.peek().is_some_and(|a| a == "--socket") {
+        args.next();
+        socket = args.next().map(PathBuf::from);
+        if socket.is_none() {
+            fail("--socket needs a path", 2);
+        }
+    }
+
+    // Path default sama dengan punini: $XDG_RUNTIME_DIR/punini.sock
+    let Some(socket) = socket.or_else(dirs::socket_path) else {
+        fail("XDG_RUNTIME_DIR is not set, use --socket <path>", 2);
+    };
+
+    let Some(cmd) = args.next() else { fail(USAGE, 2) };
+    let mut number = |name: &str| -> f64 {
+        match args.next().map(|v| v.parse::<f64>()) {
+            Some(Ok(n)) => n,
+            _ => fail(&format!("{} needs a number", name), 2),
+        }
+    };
+    let request = match cmd.as_str() {
+        "pause" | "next" | "prev" | "shuffle" | "repeat" | "quit" => json!({ "cmd": cmd }),
+        "seek" => json!({ "cmd": "seek", "secs": number("seek") }),
+        "volume" => json!({ "cmd": "volume", "value": number("volume") }),
+        _ => fail(USAGE, 2),
+    };
+
+    let mut stream = match UnixStream::connect(&socket) {
+        Ok(stream) => stream,
+        Err(e) => fail(&format!("cannot connect to {}: {}", socket.display(), e), 1),
+    };
+    if let Err(e) = writeln!(stream, "{}", request) {
+        fail(&format!("cannot send command: {}", e), 1);
+    }
+    let mut line = String::new();
+    if let Err(e) = BufReader::new(&stream).read_line(&mut line) {
+        fail(&format!("cannot read reply: {}", e), 1);
+    }
+
+    let reply: Value = serde_json::from_str(&line).unwrap_or(Value::Null);
+    match reply["status"].as_str() {
+        Some("ok") => {}
+        Some("error") => fail(reply["msg"].as_str().unwrap_or("unknown error"), 1),
+        _ => fail(&format!("unexpected reply: {}", line.trim()), 1),
+    }
+}
+
+fn fail(msg: &str, code: i32) -> ! {
+    eprintln!("punini-ctl: {}", msg);
+    process::exit(code);
+}
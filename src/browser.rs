@@ -0,0 +1,139 @@
+// Pohon folder untuk tampilan browser dua panel (tombol `v`).
+// Subfolder baru dibaca saat sebuah folder dibuka pertama kali.
+use ratatui::widgets::ListState;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+pub struct DirNode {
+    pub path: PathBuf,
+    pub name: String,
+    pub expanded: bool,
+    children: Option<Vec<DirNode>>, // None = belum dibaca
+}
+
+// Satu baris pohon yang sedang terlihat
+pub struct TreeRow {
+    pub path: PathBuf,
+    pub name: String,
+    pub depth: usize,
+    pub expanded: bool,
+    pub leaf: bool, // Sudah dibaca dan tidak punya subfolder
+}
+
+pub struct DirTree {
+    pub root: DirNode,
+    pub state: ListState,
+}
+
+impl DirNode {
+    fn new(path: PathBuf) -> Self {
+        let name = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().to_string();
+        Self { path, name, expanded: false, children: None }
+    }
+
+    fn load_children(&mut self) {
+        if self.children.is_some() {
+            return;
+        }
+        let mut dirs: Vec<PathBuf> = match fs::read_dir(&self.path) {
+            Ok(entries) => entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_dir() && !p.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')))
+                .collect(),
+            Err(e) => {
+                debug!("cannot read {}: {}", self.path.display(), e);
+                vec![]
+            }
+        };
+        dirs.sort_by_cached_key(|p| crate::sort::natural_sort_key(p));
+        self.children = Some(dirs.into_iter().map(DirNode::new).collect());
+    }
+
+    fn find_mut(&mut self, path: &Path) -> Option<&mut DirNode> {
+        if self.path == path {
+            return Some(self);
+        }
+        if !path.starts_with(&self.path) {
+            return None;
+        }
+        self.children.as_mut()?.iter_mut().find_map(|c| c.find_mut(path))
+    }
+
+    fn push_rows(&self, depth: usize, rows: &mut Vec<TreeRow>) {
+        rows.push(TreeRow {
+            path: self.path.clone(),
+            name: self.name.clone(),
+            depth,
+            expanded: self.expanded,
+            leaf: self.children.as_ref().is_some_and(|c| c.is_empty()),
+        });
+        if self.expanded
+            && let Some(children) = &self.children
+        {
+            for child in children {
+                child.push_rows(depth + 1, rows);
+            }
+        }
+    }
+}
+
+impl DirTree {
+    // Folder musik sebagai akar, langsung terbuka satu tingkat
+    pub fn new(root: PathBuf) -> Self {
+        let mut root = DirNode::new(root);
+        root.load_children();
+        root.expanded = true;
+        let mut state = ListState::default();
+        state.select(Some(0));
+        Self { root, state }
+    }
+
+    pub fn rows(&self) -> Vec<TreeRow> {
+        let mut rows = vec![];
+        self.root.push_rows(0, &mut rows);
+        rows
+    }
+
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        let i = self.state.selected()?;
+        self.rows().into_iter().nth(i).map(|r| r.path)
+    }
+
+    pub fn move_cursor(&mut self, delta: isize) {
+        let len = self.rows().len() as isize;
+        if len == 0 {
+            return;
+        }
+        let i = self.state.selected().map_or(0, |i| (i as isize + delta).rem_euclid(len));
+        self.state.select(Some(i as usize));
+    }
+
+    // Buka folder yang dipilih; path-nya dikembalikan supaya panel file bisa diisi
+    pub fn expand_selected(&mut self) -> Option<PathBuf> {
+        let path = self.selected_path()?;
+        let node = self.root.find_mut(&path)?;
+        node.load_children();
+        node.expanded = true;
+        Some(path)
+    }
+
+    // Tutup folder yang dipilih; kalau sudah tertutup, kursor pindah ke folder induk
+    pub fn collapse_selected(&mut self) {
+        let Some(path) = self.selected_path() else { return };
+        let is_root = path == self.root.path;
+        if !is_root
+            && let Some(node) = self.root.find_mut(&path)
+            && node.expanded
+        {
+            node.expanded = false;
+            return;
+        }
+        if let Some(parent) = path.parent()
+            && let Some(i) = self.rows().iter().position(|r| r.path == parent)
+        {
+            self.state.select(Some(i));
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use ringbuf::{
+    traits::{Consumer, Observer, Producer, Split},
+    HeapCons, HeapProd, HeapRb,
+};
+use rodio::source::SeekError;
+use rodio::cpal::FromSample;
+use rodio::Source;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::{io, thread};
+use std::time::Duration;
+use thread_priority::{get_current_thread_priority, set_current_thread_priority, ThreadPriority};
+use tracing::debug;
+
+// Kalau isi buffer di bawah ini, prioritas thread decode dinaikkan sementara
+const LOW_WATER_SECS: f32 = 0.5;
+// Jumlah sample yang di-decode per putaran sebelum cek permintaan seek lagi
+const CHUNK: usize = 4096;
+
+// Permintaan seek dari callback audio ke thread decode
+struct SeekRequest {
+    pos: Duration,
+    reply: Sender<Result<(), SeekError>>,
+}
+
+// Source yang dibaca Sink: hanya mengambil sample dari ring buffer,
+// decoding yang berat terjadi di thread terpisah.
+pub struct BufferedSource {
+    consumer: HeapCons<f32>,
+    channels: u16,
+    sample_rate: u32,
+    total_duration: Option<Duration>,
+    finished: Arc<AtomicBool>,
+    seek_tx: Sender<SeekRequest>,
+    resume_tx: Sender<()>,
+}
+
+impl BufferedSource {
+    // Spawn thread decode untuk `source` dan kembalikan ujung consumer-nya.
+    // Err kalau thread tidak bisa dibuat; `source` ikut terbuang
+    pub fn spawn<S>(source: S, prebuffer_secs: f32) -> io::Result<Self>
+    where
+        S: Source + Send + 'static,
+        S::Item: rodio::Sample + Send,
+        f32: FromSample<S::Item>,
+    {
+        let channels = source.channels();
+        let sample_rate = source.sample_rate();
+        let total_duration = source.total_duration();
+
+        // Kapasitas 2x prebuffer supaya producer selalu punya ruang untuk tetap di depan
+        let samples_per_sec = sample_rate as f32 * channels as f32;
+        let target = (samples_per_sec * prebuffer_secs.max(0.1)) as usize;
+        let low_water = (samples_per_sec * LOW_WATER_SECS) as usize;
+        let (producer, consumer) = HeapRb::<f32>::new(target * 2).split();
+
+        let finished = Arc::new(AtomicBool::new(false));
+        let (seek_tx, seek_rx) = mpsc::channel();
+        let (resume_tx, resume_rx) = mpsc::channel();
+
+        let worker = DecodeWorker {
+            source: source.convert_samples::<f32>(),
+            producer,
+            target,
+            low_water,
+            finished: finished.clone(),
+            seek_rx,
+            resume_rx,
+        };
+        thread::Builder::new()
+            .name("punini-decode".to_string())
+            .spawn(move || worker.run())?;
+
+        Ok(Self { consumer, channels, sample_rate, total_duration, finished, seek_tx, resume_tx })
+    }
+}
+
+impl Iterator for BufferedSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        match self.consumer.try_pop() {
+            Some(sample) => Some(sample),
+            // Decoder sudah habis: kuras sisa buffer lalu selesai
+            None if self.finished.load(Ordering::Acquire) => self.consumer.try_pop(),
+            // Underrun: isi dengan hening daripada mengakhiri lagu
+            None => Some(0.0),
+        }
+    }
+}
+
+impl Source for BufferedSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let request = SeekRequest { pos, reply: reply_tx };
+        let not_running = || SeekError::NotSupported { underlying_source: "punini decode thread" };
+
+        self.seek_tx.send(request).map_err(|_| not_running())?;
+        let result = reply_rx.recv().map_err(|_| not_running())?;
+
+        // Thread decode menunggu sampai sample lama dibuang, baru lanjut mengisi
+        self.consumer.clear();
+        let _ = self.resume_tx.send(());
+        result
+    }
+}
+
+struct DecodeWorker<S> {
+    source: S,
+    producer: HeapProd<f32>,
+    target: usize,
+    low_water: usize,
+    finished: Arc<AtomicBool>,
+    seek_rx: Receiver<SeekRequest>,
+    resume_rx: Receiver<()>,
+}
+
+impl<S: Source<Item = f32>> DecodeWorker<S> {
+    fn run(mut self) {
+        let normal_priority = get_current_thread_priority().ok();
+        let mut boosted = false;
+
+        loop {
+            // Setelah decoder habis, thread hanya menunggu seek (atau berhenti saat source di-drop)
+            let request = if self.finished.load(Ordering::Acquire) {
+                match self.seek_rx.recv() {
+                    Ok(req) => Some(req),
+                    Err(_) => return,
+                }
+            } else {
+                match self.seek_rx.try_recv() {
+                    Ok(req) => Some(req),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            };
+
+            if let Some(req) = request {
+                let result = self.source.try_seek(req.pos);
+                if result.is_ok() {
+                    self.finished.store(false, Ordering::Release);
+                }
+                if req.reply.send(result).is_err() || self.resume_rx.recv().is_err() {
+                    return;
+                }
+                continue;
+            }
+
+            // Naikkan prioritas kalau buffer hampir kosong, kembalikan saat sudah penuh lagi
+            let buffered = self.producer.occupied_len();
+            if !boosted && buffered < self.low_water {
+                // Tanpa izin (bukan root/rtkit) ini gagal; cukup coba sekali per underrun
+                if let Err(e) = set_current_thread_priority(ThreadPriority::Max) {
+                    debug!("cannot raise decode thread priority: {:?}", e);
+                }
+                boosted = true;
+            } else if boosted && buffered >= self.target {
+                if let Some(priority) = normal_priority {
+                    let _ = set_current_thread_priority(priority);
+                }
+                boosted = false;
+            }
+
+            let vacant = self.producer.vacant_len();
+            if vacant == 0 {
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+
+            for _ in 0..vacant.min(CHUNK) {
+                match self.source.next() {
+                    Some(sample) => {
+                        let _ = self.producer.try_push(sample);
+                    }
+                    None => {
+                        self.finished.store(true, Ordering::Release);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    // 1 detik mono 1 kHz, nilai 1..=1000: 0.0 hanya muncul sebagai hening saat underrun
+    fn buffered() -> BufferedSource {
+        let samples: Vec<f32> = (1..=1000).map(|i| i as f32).collect();
+        BufferedSource::spawn(SamplesBuffer::new(1, 1000, samples), 0.1).unwrap()
+    }
+
+    fn next_sample(source: &mut BufferedSource) -> Option<f32> {
+        source.find(|&sample| sample != 0.0)
+    }
+
+    #[test]
+    fn drains_every_sample_in_order() {
+        let mut source = buffered();
+        let samples: Vec<f32> = source.by_ref().filter(|&sample| sample != 0.0).collect();
+        assert_eq!(samples, (1..=1000).map(|i| i as f32).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn seek_discards_buffered_samples() {
+        let mut source = buffered();
+        assert_eq!(next_sample(&mut source), Some(1.0));
+        // Tunggu ring buffer terisi supaya ada sample lama yang harus dibuang
+        thread::sleep(Duration::from_millis(50));
+        source.try_seek(Duration::from_millis(500)).unwrap();
+        assert_eq!(next_sample(&mut source), Some(501.0));
+    }
+
+    #[test]
+    fn stays_finished_until_seek() {
+        let mut source = buffered();
+        source.by_ref().for_each(drop);
+        assert_eq!(source.next(), None);
+        assert_eq!(source.next(), None);
+        source.try_seek(Duration::ZERO).unwrap();
+        assert_eq!(next_sample(&mut source), Some(1.0));
+    }
+}
@@ -0,0 +1,31 @@
+// Cover yang sudah di-decode untuk lagu / folder album yang baru diputar (LRU kecil),
+// supaya kembali ke lagu sebelumnya tidak perlu decode gambar lagi
+use image::DynamicImage;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+const COVER_CACHE_CAPACITY: usize = 10;
+
+// Kunci: file lagu (cover embedded) atau folder album (folder.jpg dst.)
+#[derive(Default)]
+pub struct CoverCache {
+    entries: VecDeque<(PathBuf, DynamicImage)>, // Depan = paling baru dipakai
+}
+
+impl CoverCache {
+    // Hit: entry dipindah ke depan. Protocol gambar tidak bisa di-clone, jadi yang disimpan gambarnya.
+    pub fn get(&mut self, key: &Path) -> Option<DynamicImage> {
+        let i = self.entries.iter().position(|(path, _)| path == key)?;
+        let entry = self.entries.remove(i)?;
+        let img = entry.1.clone();
+        self.entries.push_front(entry);
+        Some(img)
+    }
+
+    // Entry paling lama dibuang kalau sudah penuh
+    pub fn insert(&mut self, key: PathBuf, img: DynamicImage) {
+        self.entries.retain(|(path, _)| *path != key);
+        self.entries.push_front((key, img));
+        self.entries.truncate(COVER_CACHE_CAPACITY);
+    }
+}
@@ -0,0 +1,119 @@
+// Argumen command line (clap). Prioritas tetap: CLI > env > config file > default.
+use clap::{CommandFactory, Parser};
+use clap_complete::Shell;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+#[command(name = "punini", version, about = "Terminal music player with synced lyrics")]
+pub struct Args {
+    /// Music directory (same as --dir)
+    #[arg(value_name = "DIR")]
+    pub dir_positional: Option<PathBuf>,
+
+    /// Music directory; overrides $PUNINI_MUSIC_DIR and music_dir in the config
+    #[arg(long)]
+    pub dir: Option<PathBuf>,
+
+    /// Audio output device (or part of its name)
+    #[arg(long)]
+    pub device: Option<String>,
+
+    /// Color theme (see --list-themes)
+    #[arg(long)]
+    pub theme: Option<String>,
+
+    /// Start volume, 0.0 - 1.5
+    #[arg(long, value_parser = parse_volume)]
+    pub volume: Option<f32>,
+
+    /// Play without the TUI; the music directory may also be a single file or playlist
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Start playing the first file
+    #[arg(long)]
+    pub autoplay: bool,
+
+    /// Resume the last session (track and position)
+    #[arg(long)]
+    pub restore: bool,
+
+    /// Do not resume the last session
+    #[arg(long)]
+    pub no_restore: bool,
+
+    /// Initial sort order of the file list
+    #[arg(long, value_parser = ["name", "track", "modified", "duration", "rating", "plays"])]
+    pub sort: Option<String>,
+
+    /// Show the N most played tracks instead of the music folder (default 50)
+    #[arg(long, value_name = "N", num_args = 0..=1)]
+    pub most_played: Option<Option<usize>>,
+
+    /// Log level: error, warn, info, debug, trace
+    #[arg(long, default_value = "warn")]
+    pub log_level: String,
+
+    /// Remote control socket (default: $XDG_RUNTIME_DIR/punini.sock)
+    #[arg(long)]
+    pub socket: Option<PathBuf>,
+
+    /// Print the effective config as TOML and exit
+    #[arg(long)]
+    pub print_config: bool,
+
+    /// Print the built-in color themes and exit
+    #[arg(long)]
+    pub list_themes: bool,
+
+    /// Draw every border style so you can pick one for border_style, then exit
+    #[arg(long)]
+    pub test_borders: bool,
+
+    /// Print the config, data, cache, log and socket paths and exit
+    #[arg(long)]
+    pub print_paths: bool,
+
+    /// Delete the cover thumbnail cache and exit
+    #[arg(long)]
+    pub clear_cache: bool,
+
+    /// Print the keybindings as a Markdown table and exit
+    #[arg(long)]
+    pub print_keybindings: bool,
+
+    /// Report duplicate tracks in the music directory and exit
+    #[arg(long)]
+    pub check_duplicates: bool,
+
+    /// With --check-duplicates: also same title, artist and length
+    #[arg(long)]
+    pub near: bool,
+
+    /// Check FLAC files with `flac --test` (default: the music directory) and exit
+    #[arg(long, value_name = "PATH", num_args = 0..=1)]
+    pub verify: Option<Option<PathBuf>>,
+
+    /// No colors in --verify output (also $NO_COLOR)
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Write a shell completion script to stdout and exit
+    #[arg(long, value_name = "SHELL")]
+    pub generate_completions: Option<Shell>,
+}
+
+fn parse_volume(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(volume) if (0.0..=1.5).contains(&volume) => Ok(volume),
+        _ => Err("needs a number between 0.0 and 1.5".to_string()),
+    }
+}
+
+// Ditulis ke buffer dulu: generate() panic kalau stdout ditutup (mis. `| head`)
+pub fn print_completions(shell: Shell) {
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut Args::command(), "punini", &mut script);
+    let _ = io::stdout().write_all(&script);
+}
@@ -0,0 +1,326 @@
+use crate::dirs::{home_dir, xdg_config_dir};
+use crate::theme::{self, Theme};
+use crossterm::event::KeyCode;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::debug;
+
+// Aksi yang tombolnya bisa diganti lewat [keys], beserta tombol default-nya
+const REMAPPABLE_ACTIONS: &[(&str, KeyCode)] = &[
+    ("quit", KeyCode::Char('q')),
+    ("play_pause", KeyCode::Char(' ')),
+    ("next", KeyCode::Char('n')),
+    ("prev", KeyCode::Char('N')),
+    ("shuffle", KeyCode::Char('s')),
+    ("repeat", KeyCode::Char('r')),
+    ("volume_down", KeyCode::Char('[')),
+    ("volume_up", KeyCode::Char(']')),
+    ("export_lyrics", KeyCode::Char('x')),
+    ("up", KeyCode::Char('k')),
+    ("down", KeyCode::Char('j')),
+    ("play_selected", KeyCode::Enter),
+];
+
+// Konfigurasi aplikasi, dibaca dari $XDG_CONFIG_HOME/punini/config.toml
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // Folder musik (default: $HOME/Music)
+    pub music_dir: PathBuf,
+    // Batas kedalaman subfolder saat scan folder musik
+    pub max_scan_depth: u8,
+    // Jeda antar pengecekan input (ms)
+    pub poll_interval_ms: u64,
+    // Jarak minimal antar gambar ulang UI (ms); 33 = ~30 fps
+    pub render_interval_ms: u64,
+    // Warna highlight daftar file (nama warna ratatui atau #rrggbb)
+    pub highlight_color: String,
+    // Volume awal, 0.0 - 1.5
+    pub volume: f32,
+    // Sembunyikan baris LRC kosong (spacer antar bait) dari panel lirik
+    pub lrc_hide_blank_lines: bool,
+    // Berapa detik audio yang di-decode di depan posisi playback
+    pub prebuffer_secs: f32,
+    // Bedakan elemen UI dengan pola & modifier, bukan warna (untuk buta warna)
+    pub accessibility_mode: bool,
+    // Kontras tinggi: teks abu-abu jadi putih / kuning, border putih (tombol `H`)
+    pub high_contrast: bool,
+    // Jenis garis border panel: plain, rounded, double, thick, quadrant_inside, quadrant_outside, ascii
+    pub border_style: BorderStyle,
+    // Apa yang dilakukan saat aplikasi baru dibuka
+    pub startup_action: StartupAction,
+    // Notifikasi desktop saat ganti lagu (hanya kalau dibuild dengan fitur `notify`)
+    pub notify: bool,
+    // Tampilkan lagu & status play/pause di judul window terminal
+    pub update_title: bool,
+    // Judul yang lebih panjang dari panel Info bergulir (marquee), bukan terpotong
+    pub scroll_titles: bool,
+    // Mouse: klik progress bar untuk seek, scroll di panel player untuk volume.
+    // Matikan kalau ingin select teks terminal seperti biasa.
+    pub mouse: bool,
+    // Selisih (ms) posisi estimasi vs posisi sink sebelum progress bar disamakan lagi
+    pub position_resync_ms: u64,
+    // Folder tujuan `W` (simpan cover lagu sebagai PNG)
+    pub cover_save_dir: PathBuf,
+    // Skema warna: preset + warna per peran
+    pub theme: ThemeConfig,
+    // Ganti tombol: nama aksi -> tombol, mis. quit = "Q", play_pause = "p"
+    pub keys: BTreeMap<String, String>,
+
+    // Hasil olahan `keys`: tombol yang ditekan -> tombol default aksinya
+    #[serde(skip)]
+    key_remap: HashMap<KeyCode, KeyCode>,
+}
+
+// [theme]: name = "nord", lalu opsional ganti peran tertentu, mis. progress_fill = "#ff8800"
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    // Preset bawaan: default, nord, gruvbox
+    pub name: String,
+    #[serde(flatten)]
+    pub colors: BTreeMap<String, String>,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self { name: "default".to_string(), colors: BTreeMap::new() }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupAction {
+    // Tampilkan daftar file saja, belum ada yang diputar
+    ShowBrowser,
+    // Langsung putar file pertama
+    AutoPlay,
+    // Lanjutkan sesi terakhir (lagu & posisi); tanpa sesi sama dengan ShowBrowser
+    RestoreSession,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BorderStyle {
+    Plain,
+    Rounded,
+    Double,
+    Thick,
+    QuadrantInside,
+    QuadrantOutside,
+    // + - | untuk font / terminal tanpa karakter box-drawing
+    Ascii,
+}
+
+impl BorderStyle {
+    pub const ALL: [BorderStyle; 7] = [
+        BorderStyle::Plain,
+        BorderStyle::Rounded,
+        BorderStyle::Double,
+        BorderStyle::Thick,
+        BorderStyle::QuadrantInside,
+        BorderStyle::QuadrantOutside,
+        BorderStyle::Ascii,
+    ];
+
+    // Sama dengan nilai di config.toml
+    pub fn name(self) -> &'static str {
+        match self {
+            BorderStyle::Plain => "plain",
+            BorderStyle::Rounded => "rounded",
+            BorderStyle::Double => "double",
+            BorderStyle::Thick => "thick",
+            BorderStyle::QuadrantInside => "quadrant_inside",
+            BorderStyle::QuadrantOutside => "quadrant_outside",
+            BorderStyle::Ascii => "ascii",
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            music_dir: home_dir().join("Music"),
+            max_scan_depth: 5,
+            poll_interval_ms: 50,
+            render_interval_ms: 33,
+            highlight_color: "blue".to_string(),
+            volume: 1.0,
+            lrc_hide_blank_lines: true,
+            prebuffer_secs: 2.0,
+            accessibility_mode: false,
+            high_contrast: false,
+            border_style: BorderStyle::Plain,
+            startup_action: StartupAction::RestoreSession,
+            notify: true,
+            update_title: true,
+            scroll_titles: true,
+            mouse: true,
+            position_resync_ms: 500,
+            cover_save_dir: home_dir().join("Pictures"),
+            theme: ThemeConfig::default(),
+            keys: BTreeMap::new(),
+            key_remap: HashMap::new(),
+        }
+    }
+}
+
+impl Config {
+    // Baca config dari disk. File tidak ada = default (dan tulis contoh config).
+    // Isi yang tidak valid dikembalikan sebagai pesan error yang jelas.
+    pub fn load() -> Result<Self, String> {
+        let path = config_path();
+        let mut config = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+            toml::from_str::<Config>(&content)
+                .map_err(|e| format!("invalid config {}: {}", path.display(), e))?
+        } else {
+            // Gagal menulis contoh config bukan masalah besar
+            if let Err(e) = write_example(&path) {
+                debug!("cannot write example config {}: {}", path.display(), e);
+            }
+            Config::default()
+        };
+        config.music_dir = expand_tilde(&config.music_dir);
+        config.cover_save_dir = expand_tilde(&config.cover_save_dir);
+        config.validate().map_err(|e| format!("invalid config {}: {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    fn validate(&mut self) -> Result<(), String> {
+        if !(0.0..=1.5).contains(&self.volume) {
+            return Err(format!("volume must be between 0.0 and 1.5, got {}", self.volume));
+        }
+        if self.prebuffer_secs <= 0.0 {
+            return Err(format!("prebuffer_secs must be positive, got {}", self.prebuffer_secs));
+        }
+        if self.poll_interval_ms == 0 {
+            return Err("poll_interval_ms must be at least 1".to_string());
+        }
+        if self.render_interval_ms == 0 {
+            return Err("render_interval_ms must be at least 1".to_string());
+        }
+        self.highlight()?;
+        self.theme()?;
+
+        // Tombol default dari aksi yang diganti dimatikan dulu, lalu tombol baru diarahkan ke sana
+        let mut remap = HashMap::new();
+        for (action, key) in &self.keys {
+            let Some((_, default)) = REMAPPABLE_ACTIONS.iter().find(|(name, _)| name == action) else {
+                let names: Vec<&str> = REMAPPABLE_ACTIONS.iter().map(|(name, _)| *name).collect();
+                return Err(format!("unknown action '{}' in [keys] (expected one of: {})", action, names.join(", ")));
+            };
+            let code = parse_key(key).ok_or_else(|| format!("unknown key '{}' for action '{}'", key, action))?;
+            remap.entry(*default).or_insert(KeyCode::Null);
+            remap.insert(code, *default);
+        }
+        self.key_remap = remap;
+        Ok(())
+    }
+
+    // Warna highlight yang sudah divalidasi
+    pub fn highlight(&self) -> Result<Color, String> {
+        Color::from_str(&self.highlight_color)
+            .map_err(|_| format!("unknown color '{}' for highlight_color", self.highlight_color))
+    }
+
+    // Preset [theme] + warna per peran. highlight_color tetap berlaku untuk preset default.
+    pub fn theme(&self) -> Result<Theme, String> {
+        let mut theme = theme::find(&self.theme.name).ok_or_else(|| {
+            format!("unknown theme '{}' (expected one of: {})", self.theme.name, theme::names().join(", "))
+        })?;
+        if theme == theme::DEFAULT {
+            theme.highlight_bg = self.highlight()?;
+            theme.border_active = theme.highlight_bg;
+        }
+        for (role, value) in &self.theme.colors {
+            let color = Color::from_str(value).map_err(|_| format!("unknown color '{}' for theme.{}", value, role))?;
+            if !theme.set(role, color) {
+                return Err(format!("unknown color role '{}' in [theme] (expected one of: {})", role, theme::ROLES.join(", ")));
+            }
+        }
+        Ok(theme)
+    }
+
+    // Terjemahkan tombol yang ditekan sesuai [keys]
+    pub fn remap(&self, code: KeyCode) -> KeyCode {
+        self.key_remap.get(&code).copied().unwrap_or(code)
+    }
+
+    // Config efektif dalam format TOML (untuk --print-config)
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap_or_default()
+    }
+}
+
+pub fn config_path() -> PathBuf {
+    xdg_config_dir().join("config.toml")
+}
+
+fn expand_tilde(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => home_dir().join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+// Contoh config: semua nilai default, dikomentari
+fn write_example(path: &Path) -> std::io::Result<()> {
+    let mut content = String::from("# Punini config. Hapus tanda '#' untuk mengganti nilai default.\n\n");
+    // Tabel [keys] kosong diganti contoh di bawah
+    for line in Config::default().to_toml().lines().filter(|l| !l.is_empty() && *l != "[keys]") {
+        content.push_str(&format!("# {}\n", line));
+    }
+    content.push_str("\n# [keys]\n# quit = \"Q\"\n# play_pause = \"p\"\n");
+    fs::write(path, content)
+}
+
+// Simpan satu nilai (mis. dari tombol di TUI) ke config.toml. Baris lain, termasuk komentar,
+// tidak diubah; key baru ditaruh sebelum tabel pertama supaya tetap di level atas.
+pub fn save_value(key: &str, value: &str) -> Result<(), String> {
+    let path = config_path();
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    let entry = format!("{} = {}", key, value);
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let is_key = |line: &str| line.split('=').next().is_some_and(|k| k.trim() == key);
+    if let Some(line) = lines.iter_mut().find(|l| is_key(l)) {
+        *line = entry;
+    } else {
+        let mut at = lines.iter().position(|l| l.trim_start().starts_with('[')).unwrap_or(lines.len());
+        while at > 0 && lines[at - 1].trim().is_empty() {
+            at -= 1;
+        }
+        lines.insert(at, entry);
+    }
+    fs::write(&path, lines.join("\n") + "\n").map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+// "q", "space", "enter", "pageup", ...
+fn parse_key(key: &str) -> Option<KeyCode> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let code = match key.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        _ => return None,
+    };
+    Some(code)
+}
@@ -0,0 +1,111 @@
+// CUE sheet: satu file audio besar (album.flac + album.cue) dibagi jadi lagu-lagu virtual
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tracing::debug;
+
+// Frame CUE = 1/75 detik
+const FRAMES_PER_SEC: u64 = 75;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CueTrack {
+    pub title: String,
+    pub artist: Option<String>,  // PERFORMER lagu, atau PERFORMER album
+    pub start: Duration,         // INDEX 01
+    pub end: Option<Duration>,   // Awal lagu berikutnya; None = sampai akhir file
+}
+
+// Cari `<stem>.cue` di sebelah file audio. None kalau tidak ada / tidak berisi lagu.
+pub fn load_for(audio: &Path) -> Option<Vec<CueTrack>> {
+    let cue_path = audio.with_extension("cue");
+    if !cue_path.is_file() {
+        return None;
+    }
+    // Banyak CUE lama bukan UTF-8; karakter rusak lebih baik daripada gagal total
+    let content = match fs::read(&cue_path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(e) => {
+            debug!("cannot read {}: {}", cue_path.display(), e);
+            return None;
+        }
+    };
+    let tracks = parse_cue(&content);
+    (!tracks.is_empty()).then_some(tracks)
+}
+
+// Lagu yang sedang dibaca, sebelum TRACK berikutnya
+#[derive(Default)]
+struct PendingTrack {
+    number: u32,
+    title: Option<String>,
+    artist: Option<String>,
+    start: Option<Duration>,
+}
+
+pub fn parse_cue(content: &str) -> Vec<CueTrack> {
+    let mut album_artist = None;
+    let mut current: Option<PendingTrack> = None;
+    let mut tracks: Vec<CueTrack> = vec![];
+
+    for line in content.lines() {
+        let line = line.trim();
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        match command.to_ascii_uppercase().as_str() {
+            "TRACK" => {
+                push_track(current.take(), &album_artist, &mut tracks);
+                let number = rest.split_whitespace().next().and_then(|n| n.parse().ok()).unwrap_or(0);
+                current = Some(PendingTrack { number, ..Default::default() });
+            }
+            "TITLE" => {
+                if let Some(track) = &mut current {
+                    track.title = Some(unquote(rest));
+                }
+            }
+            "PERFORMER" => match &mut current {
+                Some(track) => track.artist = Some(unquote(rest)),
+                None => album_artist = Some(unquote(rest)),
+            },
+            "INDEX" => {
+                let mut parts = rest.split_whitespace();
+                if let (Some("01"), Some(time), Some(track)) = (parts.next(), parts.next(), &mut current) {
+                    track.start = parse_time(time);
+                }
+            }
+            _ => {}
+        }
+    }
+    push_track(current, &album_artist, &mut tracks);
+
+    // Akhir lagu = awal lagu berikutnya
+    let starts: Vec<Duration> = tracks.iter().map(|t| t.start).collect();
+    for (track, next_start) in tracks.iter_mut().zip(starts.into_iter().skip(1)) {
+        track.end = Some(next_start);
+    }
+    tracks
+}
+
+// Lagu tanpa INDEX 01 tidak bisa diputar, dilewati
+fn push_track(track: Option<PendingTrack>, album_artist: &Option<String>, tracks: &mut Vec<CueTrack>) {
+    let Some(PendingTrack { number, title, artist, start: Some(start) }) = track else { return };
+    tracks.push(CueTrack {
+        title: title.unwrap_or_else(|| format!("Track {}", number)),
+        artist: artist.or_else(|| album_artist.clone()),
+        start,
+        end: None,
+    });
+}
+
+fn unquote(s: &str) -> String {
+    s.strip_prefix('"').and_then(|s| s.strip_suffix('"')).unwrap_or(s).to_string()
+}
+
+// "mm:ss:ff" -> Duration
+fn parse_time(time: &str) -> Option<Duration> {
+    let mut parts = time.split(':').map(|p| p.parse::<u64>().ok());
+    let (Some(Some(min)), Some(Some(sec)), Some(Some(frames)), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    Some(Duration::from_secs(min * 60 + sec) + Duration::from_millis(frames * 1000 / FRAMES_PER_SEC))
+}
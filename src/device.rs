@@ -0,0 +1,49 @@
+// Pilih output audio: semua device dari semua host cpal (ALSA, JACK, ...)
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::cpal::{self, Device};
+use rodio::{OutputStream, OutputStreamHandle};
+use tracing::debug;
+
+// (nama, device) untuk semua output yang bisa dibuka; nama kembar dari host lain tetap didaftar
+pub fn output_devices() -> Vec<(String, Device)> {
+    let mut devices = vec![];
+    for id in cpal::available_hosts() {
+        let host = match cpal::host_from_id(id) {
+            Ok(host) => host,
+            Err(e) => {
+                debug!("audio host {:?} unavailable: {}", id, e);
+                continue;
+            }
+        };
+        let Ok(outputs) = host.output_devices() else { continue };
+        devices.extend(outputs.filter_map(|d| Some((d.name().ok()?, d))));
+    }
+    devices
+}
+
+pub fn names() -> Vec<String> {
+    output_devices().into_iter().map(|(name, _)| name).collect()
+}
+
+// Device pertama yang namanya mengandung `query` (tanpa beda huruf besar/kecil)
+pub fn find(query: &str) -> Option<(String, Device)> {
+    let query = query.to_lowercase();
+    output_devices().into_iter().find(|(name, _)| name.to_lowercase().contains(&query))
+}
+
+// Buka stream di device yang namanya cocok, atau device default kalau `query` None.
+// Hasilnya juga nama device yang benar-benar dipakai, untuk ditandai di menu `D`.
+pub fn open(query: Option<&str>) -> Result<(OutputStream, OutputStreamHandle, Option<String>), String> {
+    match query {
+        None => {
+            let name = cpal::default_host().default_output_device().and_then(|d| d.name().ok());
+            let (stream, handle) = OutputStream::try_default().map_err(|e| e.to_string())?;
+            Ok((stream, handle, name))
+        }
+        Some(query) => {
+            let (name, device) = find(query).ok_or_else(|| format!("no output device matching '{}'", query))?;
+            let (stream, handle) = OutputStream::try_from_device(&device).map_err(|e| format!("{}: {}", name, e))?;
+            Ok((stream, handle, Some(name)))
+        }
+    }
+}
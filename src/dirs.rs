@@ -0,0 +1,45 @@
+// Folder punini menurut XDG Base Directory: config, data, cache, state (+ socket di runtime dir).
+// Nilai env yang kosong atau relatif diabaikan (sesuai spec), lalu fallback ke ~/...
+use std::fs;
+use std::path::PathBuf;
+
+pub const SOCKET_FILE: &str = "punini.sock";
+
+pub fn home_dir() -> PathBuf {
+    std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default()
+}
+
+// $<var>/punini atau ~/<fallback>/punini; folder dibuat kalau belum ada
+fn xdg_dir(var: &str, fallback: &[&str]) -> PathBuf {
+    let dir = std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| fallback.iter().fold(home_dir(), |dir, part| dir.join(part)))
+        .join("punini");
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+pub fn xdg_config_dir() -> PathBuf {
+    xdg_dir("XDG_CONFIG_HOME", &[".config"])
+}
+
+pub fn xdg_data_dir() -> PathBuf {
+    xdg_dir("XDG_DATA_HOME", &[".local", "share"])
+}
+
+pub fn xdg_cache_dir() -> PathBuf {
+    xdg_dir("XDG_CACHE_HOME", &[".cache"])
+}
+
+pub fn xdg_state_dir() -> PathBuf {
+    xdg_dir("XDG_STATE_HOME", &[".local", "state"])
+}
+
+// $XDG_RUNTIME_DIR/punini.sock; None kalau XDG_RUNTIME_DIR tidak di-set (tidak ada fallback yang aman)
+pub fn socket_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .map(|p| p.join(SOCKET_FILE))
+}
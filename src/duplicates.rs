@@ -0,0 +1,167 @@
+// Mode --check-duplicates: cari lagu kembar di library lalu cetak laporan, tanpa TUI
+use crate::library::{read_meta, TrackMeta};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::debug;
+
+// Cukup awal data audio yang di-hash: sudah membedakan lagu, dan cepat untuk library besar
+const HASH_BYTES: u64 = 64 * 1024;
+// Near-duplicate (format / bitrate lain): selisih durasi maksimal
+const NEAR_DURATION_TOLERANCE: Duration = Duration::from_secs(2);
+
+struct Scanned {
+    path: PathBuf,
+    size: u64,
+    hash: Option<blake3::Hash>, // None = file tidak bisa dibaca
+    meta: TrackMeta,
+}
+
+// `near` = laporkan juga lagu sama beda file (judul + artis sama, durasi ±2 detik)
+pub fn run(files: Vec<PathBuf>, near: bool) {
+    eprintln!("Hashing {} files…", files.len());
+    let scanned: Vec<Scanned> = files
+        .into_par_iter()
+        .map(|path| {
+            let hash = audio_hash(&path)
+                .map_err(|e| debug!("cannot hash {}: {}", path.display(), e))
+                .ok();
+            let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let meta = read_meta(&path);
+            Scanned { path, size, hash, meta }
+        })
+        .collect();
+
+    // 1. Kembar persis: hash audio sama
+    let mut by_hash: HashMap<blake3::Hash, Vec<usize>> = HashMap::new();
+    for (i, s) in scanned.iter().enumerate() {
+        if let Some(hash) = s.hash {
+            by_hash.entry(hash).or_default().push(i);
+        }
+    }
+    let mut sets: Vec<(&str, Vec<usize>)> = by_hash
+        .into_values()
+        .filter(|set| set.len() > 1)
+        .map(|set| ("identical audio", set))
+        .collect();
+
+    // 2. Near-duplicate
+    if near {
+        sets.extend(near_duplicates(&scanned).into_iter().map(|set| ("same title, artist & length", set)));
+    }
+
+    if sets.is_empty() {
+        println!("No duplicates found in {} files", scanned.len());
+        return;
+    }
+
+    // 3. Laporan per album (album file pertama di set), supaya mudah memilih salinan yang disimpan
+    let total = sets.len();
+    let mut by_album: BTreeMap<String, Vec<(&str, Vec<usize>)>> = BTreeMap::new();
+    for (kind, mut set) in sets {
+        set.sort_by(|&a, &b| scanned[a].path.cmp(&scanned[b].path));
+        let album = scanned[set[0]].meta.album.clone()
+            .filter(|a| !a.trim().is_empty())
+            .unwrap_or_else(|| "(unknown album)".to_string());
+        by_album.entry(album).or_default().push((kind, set));
+    }
+    println!("{} duplicate sets in {} files\n", total, scanned.len());
+    for (album, sets) in &by_album {
+        println!("{}", album);
+        for (kind, set) in sets {
+            println!("  {} files, {}:", set.len(), kind);
+            for &i in set {
+                let size = scanned[i].size as f64 / (1024.0 * 1024.0);
+                println!("    {}  ({:.1} MB)", scanned[i].path.display(), size);
+            }
+        }
+        println!();
+    }
+}
+
+fn near_duplicates(scanned: &[Scanned]) -> Vec<Vec<usize>> {
+    let duration = |i: usize| scanned[i].meta.duration.unwrap_or_default();
+    let mut by_name: HashMap<(String, String), Vec<usize>> = HashMap::new();
+    for (i, s) in scanned.iter().enumerate() {
+        if let (Some(title), Some(artist), Some(_)) = (&s.meta.title, &s.meta.artist, s.meta.duration) {
+            by_name.entry((title.trim().to_lowercase(), artist.trim().to_lowercase())).or_default().push(i);
+        }
+    }
+
+    let mut sets = vec![];
+    for mut group in by_name.into_values().filter(|g| g.len() > 1) {
+        // Urut durasi, lalu dipecah di celah yang lebih besar dari toleransi
+        group.sort_by_key(|&i| duration(i));
+        let mut current: Vec<usize> = vec![];
+        for i in group {
+            if let Some(&last) = current.last()
+                && duration(i) - duration(last) > NEAR_DURATION_TOLERANCE
+            {
+                push_near_set(&mut sets, std::mem::take(&mut current), scanned);
+            }
+            current.push(i);
+        }
+        push_near_set(&mut sets, current, scanned);
+    }
+    sets
+}
+
+// Set yang audionya identik semua sudah dilaporkan sebagai kembar persis
+fn push_near_set(sets: &mut Vec<Vec<usize>>, set: Vec<usize>, scanned: &[Scanned]) {
+    let first = scanned[set[0]].hash;
+    if set.len() > 1 && (first.is_none() || set.iter().any(|&i| scanned[i].hash != first)) {
+        sets.push(set);
+    }
+}
+
+// Hash HASH_BYTES pertama data audio. Tag di depan file dilewati,
+// jadi file yang cuma beda tag tetap terdeteksi kembar.
+fn audio_hash(path: &Path) -> io::Result<blake3::Hash> {
+    let mut file = File::open(path)?;
+    let offset = audio_offset(&mut file)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut hasher = blake3::Hasher::new();
+    io::copy(&mut file.take(HASH_BYTES), &mut hasher)?;
+    Ok(hasher.finalize())
+}
+
+// Awal data audio: setelah ID3v2 (MP3, kadang juga FLAC) dan blok metadata FLAC.
+// Format lain di-hash dari byte pertama.
+fn audio_offset(file: &mut File) -> io::Result<u64> {
+    let mut offset = 0;
+
+    // ID3v2: header 10 byte, ukuran syncsafe (4 x 7 bit), +10 kalau ada footer
+    let mut header = [0u8; 10];
+    if read_at(file, 0, &mut header)? && &header[..3] == b"ID3" {
+        let size = header[6..10].iter().fold(0u64, |acc, &b| (acc << 7) | (b & 0x7f) as u64);
+        let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+        offset = 10 + size + footer;
+    }
+
+    // FLAC: "fLaC" lalu blok metadata; header blok 4 byte (bit 7 = blok terakhir, 24 bit panjang)
+    let mut magic = [0u8; 4];
+    if read_at(file, offset, &mut magic)? && &magic == b"fLaC" {
+        offset += 4;
+        let mut block = [0u8; 4];
+        while read_at(file, offset, &mut block)? {
+            offset += 4 + u32::from_be_bytes([0, block[1], block[2], block[3]]) as u64;
+            if block[0] & 0x80 != 0 {
+                break;
+            }
+        }
+    }
+    Ok(offset)
+}
+
+// false kalau file terlalu pendek untuk mengisi `buf`
+fn read_at(file: &mut File, offset: u64, buf: &mut [u8]) -> io::Result<bool> {
+    file.seek(SeekFrom::Start(offset))?;
+    match file.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,113 @@
+// Mode --headless: putar tanpa TUI, kontrol lewat stdin (satu perintah per baris)
+use crate::{is_playlist_file, parse_m3u, AppState};
+use anyhow::Result;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+// Argumen berupa file: playlist -> isinya, lagu -> satu lagu itu saja
+pub fn files_from(path: &Path) -> Vec<PathBuf> {
+    if !is_playlist_file(path) {
+        return vec![path.to_path_buf()];
+    }
+    let content = match std::fs::read(path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(e) => {
+            warn!("failed to read playlist {}: {}", path.display(), e);
+            return vec![];
+        }
+    };
+    let (entries, skipped) = parse_m3u(&content, path.parent().unwrap_or(Path::new("")));
+    for entry in &skipped {
+        warn!("playlist {}: skipping missing entry {}", path.display(), entry);
+    }
+    entries.into_iter().map(|(file, _)| file).collect()
+}
+
+pub fn run(app: &mut AppState) -> Result<()> {
+    if app.files.is_empty() {
+        eprintln!("No audio files found");
+        return Ok(());
+    }
+
+    // stdin dibaca di thread sendiri supaya loop utama tidak ikut terblokir
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("punini-stdin".to_string())
+        .spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        })?;
+
+    app.play_index(0);
+    let poll = Duration::from_millis(app.config.poll_interval_ms);
+    // Lagu terakhir yang dicetak; lagu CUE dihitung per lagu virtual
+    let mut announced: Option<(PathBuf, Option<usize>)> = None;
+    let mut stdin_open = true;
+
+    loop {
+        if app.shutdown.load(Ordering::Relaxed) {
+            info!("received termination signal, shutting down");
+            return Ok(());
+        }
+        app.tick();
+
+        // Satu baris per ganti lagu, setelah metadata dari worker masuk
+        let now_playing = app.current_path.clone().map(|p| (p, app.current_cue));
+        if app.meta_ready && now_playing != announced {
+            announced = now_playing;
+            let secs = app.cue_span().map_or(app.duration, |(_, len)| len).as_secs();
+            println!("Playing: {} – {} ({}:{:02})", app.artist, app.title, secs / 60, secs % 60);
+        }
+        if let Some(msg) = app.status.take() {
+            println!("{}", msg);
+        }
+        if let Some(err) = app.last_error.take() {
+            eprintln!("Error: {}", err);
+        }
+
+        // Playlist habis (repeat off) atau lagu gagal diputar: selesai
+        if !app.track_was_loaded && app.sink.empty() {
+            return Ok(());
+        }
+
+        if !stdin_open {
+            thread::sleep(poll);
+            continue;
+        }
+        let line = match rx.recv_timeout(poll) {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Timeout) => continue,
+            // stdin ditutup: tetap putar sampai habis
+            Err(RecvTimeoutError::Disconnected) => {
+                stdin_open = false;
+                continue;
+            }
+        };
+        let line = line.trim();
+        match line {
+            "" => {}
+            "q" => return Ok(()),
+            "p" => app.toggle_pause(),
+            "n" => app.next_track(),
+            "s" => {
+                app.toggle_shuffle();
+                println!("Shuffle: {}", if app.shuffle { "on" } else { "off" });
+            }
+            "r" => {
+                app.repeat = app.repeat.next();
+                println!("Repeat: {:?}", app.repeat);
+            }
+            _ if line == "t" || line.starts_with("t ") => app.run_command(line),
+            _ => eprintln!("Unknown command: {} (p, n, s, r, t <min>, q)", line),
+        }
+    }
+}
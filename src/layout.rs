@@ -0,0 +1,20 @@
+// Lebar panel mengikuti ukuran terminal
+use ratatui::layout::{Constraint, Rect};
+
+// Terminal lebih sempit dari ini: panel cover disembunyikan, info & lirik memakai seluruh player
+const MIN_COVER_WIDTH: u16 = 80;
+
+// File browser / player: (kiri, kanan)
+pub fn compute_layout(area: Rect) -> (Constraint, Constraint) {
+    match area.width {
+        0..100 => (Constraint::Percentage(25), Constraint::Percentage(75)),
+        100..150 => (Constraint::Percentage(30), Constraint::Percentage(70)),
+        _ => (Constraint::Percentage(40), Constraint::Percentage(60)),
+    }
+}
+
+// Cover / (info & lirik) di dalam player; None = tanpa panel cover.
+// `area` = seluruh terminal, bukan panel player.
+pub fn cover_layout(area: Rect) -> Option<(Constraint, Constraint)> {
+    (area.width >= MIN_COVER_WIDTH).then_some((Constraint::Percentage(35), Constraint::Percentage(65)))
+}
@@ -0,0 +1,118 @@
+use lofty::prelude::*;
+use lofty::file::TaggedFile;
+use lofty::probe::Probe;
+use lofty::tag::ItemKey;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+// Metadata ringan per file untuk sorting & tampilan file list
+#[derive(Clone, Debug, Default)]
+pub struct TrackMeta {
+    pub duration: Option<Duration>,
+    pub track_number: Option<u32>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub genre: Option<String>,
+    pub bitrate: Option<u32>, // kbps
+    pub has_cover: bool,      // Ada cover embedded
+    pub has_lyrics: bool,     // Lirik embedded atau file .lrc / .srt / .vtt
+}
+
+pub type MetaCache = Arc<Mutex<HashMap<PathBuf, TrackMeta>>>;
+
+// Isi cache di thread terpisah supaya UI tidak menunggu Lofty.
+// File yang sudah ada di cache dilewati.
+pub fn spawn_meta_scan(files: Vec<PathBuf>, cache: MetaCache) {
+    // Kalau thread gagal dibuat, file list tetap jalan hanya tanpa metadata
+    let spawned = thread::Builder::new()
+        .name("punini-meta".to_string())
+        .spawn(move || {
+            for path in files {
+                if cache.lock().is_ok_and(|c| c.contains_key(&path)) {
+                    continue;
+                }
+                // Lock hanya saat insert; baca tag dilakukan tanpa lock
+                let meta = read_meta(&path);
+                if let Ok(mut c) = cache.lock() {
+                    c.insert(path, meta);
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        warn!("cannot spawn metadata scan thread: {}", e);
+    }
+}
+
+// Baca file lewat Lofty. Format ditebak dari isi file, bukan ekstensi saja:
+// Opus kadang disimpan sebagai .ogg, yang oleh Lofty dianggap Vorbis.
+pub fn read_tagged(path: &Path) -> lofty::error::Result<TaggedFile> {
+    Probe::open(path)?.guess_file_type()?.read()
+}
+
+pub fn read_meta(path: &Path) -> TrackMeta {
+    let tagged_file = match read_tagged(path) {
+        Ok(tagged_file) => tagged_file,
+        Err(e) => {
+            debug!("cannot read tags of {}: {}", path.display(), e);
+            return TrackMeta::default();
+        }
+    };
+    let tag = tagged_file.primary_tag();
+    let lyrics_tag = tag.is_some_and(|t| t.get_string(&ItemKey::Lyrics).is_some());
+    TrackMeta {
+        duration: Some(tagged_file.properties().duration()),
+        track_number: tag.and_then(|t| t.track()),
+        title: tag.and_then(|t| t.title()).map(|s| s.to_string()),
+        artist: tag.and_then(|t| t.artist()).map(|s| s.to_string()),
+        album: tag.and_then(|t| t.album()).map(|s| s.to_string()),
+        genre: tag.and_then(|t| t.genre()).map(|s| s.to_string()),
+        bitrate: tagged_file.properties().audio_bitrate(),
+        has_cover: tag.is_some_and(|t| !t.pictures().is_empty()),
+        has_lyrics: lyrics_tag || ["lrc", "srt", "vtt"].iter().any(|ext| path.with_extension(ext).exists()),
+    }
+}
+
+// Ringkasan library untuk layar statistik `S`
+#[derive(Debug, Default)]
+pub struct CollectionStats {
+    pub tracks: usize,
+    pub loaded: usize, // File yang metadatanya sudah ada di cache; < tracks = masih di-scan
+    pub total_duration: Duration,
+    pub formats: BTreeMap<String, usize>, // "FLAC" -> jumlah file
+    pub avg_bitrate: Option<u32>,         // kbps, dari file yang bitrate-nya diketahui
+    pub with_cover: usize,
+    pub with_lyrics: usize,
+}
+
+impl CollectionStats {
+    pub fn complete(&self) -> bool {
+        self.loaded >= self.tracks
+    }
+}
+
+// Hitung dari cache; file yang belum terbaca hanya masuk hitungan jumlah & format
+pub fn collection_stats(files: &[PathBuf], cache: &MetaCache) -> CollectionStats {
+    let mut stats = CollectionStats { tracks: files.len(), ..Default::default() };
+    let (mut bitrate_sum, mut bitrate_count) = (0u64, 0u64);
+    let Ok(cache) = cache.lock() else { return stats };
+    for path in files {
+        let format = path.extension().map(|e| e.to_string_lossy().to_uppercase()).unwrap_or_default();
+        *stats.formats.entry(format).or_insert(0) += 1;
+        let Some(meta) = cache.get(path) else { continue };
+        stats.loaded += 1;
+        stats.total_duration += meta.duration.unwrap_or_default();
+        if let Some(bitrate) = meta.bitrate {
+            bitrate_sum += bitrate as u64;
+            bitrate_count += 1;
+        }
+        stats.with_cover += meta.has_cover as usize;
+        stats.with_lyrics += meta.has_lyrics as usize;
+    }
+    stats.avg_bitrate = (bitrate_count > 0).then(|| (bitrate_sum / bitrate_count) as u32);
+    stats
+}
@@ -0,0 +1,35 @@
+// Log ke file: di mode raw terminal, stderr tidak kelihatan
+use crate::dirs::xdg_state_dir;
+use std::fs;
+use std::path::PathBuf;
+use tracing_subscriber::EnvFilter;
+
+pub const LOG_FILE_PREFIX: &str = "punini.log";
+// File log lebih besar dari ini dikosongkan saat start
+const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+// Pasang subscriber `tracing` dengan file harian. Level tidak valid = Err
+pub fn init(level: &str) -> Result<(), String> {
+    let filter = EnvFilter::try_new(level).map_err(|e| format!("invalid --log-level '{}': {}", level, e))?;
+    let dir = xdg_state_dir();
+    truncate_oversized(&dir);
+
+    let appender = tracing_appender::rolling::daily(&dir, LOG_FILE_PREFIX);
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(appender)
+        .with_ansi(false)
+        .init();
+    Ok(())
+}
+
+// Rotasi harian membuat punini.log.YYYY-MM-DD; kosongkan yang melewati batas
+fn truncate_oversized(dir: &PathBuf) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let oversized = entry.metadata().is_ok_and(|m| m.len() > MAX_LOG_BYTES);
+        if oversized && entry.file_name().to_string_lossy().starts_with(LOG_FILE_PREFIX) {
+            let _ = fs::File::create(entry.path());
+        }
+    }
+}
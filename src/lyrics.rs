@@ -0,0 +1,335 @@
+// Lirik: LRC (termasuk kata per kata A2), teks biasa tanpa timestamp, subtitle SRT / WebVTT
+use once_cell::sync::Lazy;
+use regex::Regex;
+use unicode_bidi::{bidi_class, BidiClass, BidiInfo};
+use std::borrow::Cow;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct LyricLine {
+    pub time: Duration,
+    pub text: String,
+    // Timestamp per kata (format A2 / enhanced LRC), kalau ada
+    pub words: Option<Vec<LyricWord>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LyricWord {
+    pub start: Duration,
+    pub text: String,
+}
+
+// Format sama seperti LRC: [mm:ss.cc] text
+impl fmt::Display for LyricLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.time.as_secs();
+        let centis = self.time.subsec_millis() / 10;
+        write!(f, "[{:02}:{:02}.{:02}] {}", secs / 60, secs % 60, centis, self.text)
+    }
+}
+
+// Header metadata di file LRC: [ar:], [ti:], [al:], [by:]
+#[derive(Clone, Debug, Default)]
+pub struct LrcMetadata {
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub album: Option<String>,
+    pub creator: Option<String>,
+}
+
+// Isi file / tag lirik mentah dari worker, formatnya dari asal lirik
+#[derive(Debug)]
+pub enum LyricsSource {
+    // File .lrc atau lirik embedded: LRC, atau teks biasa tanpa timestamp
+    Lrc(String),
+    Srt(String),
+    Vtt(String),
+}
+
+// Satu timestamp di awal string; dipakai berulang untuk [01:23.45][02:34.56]Teks
+static LRC_TIME_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\[(\d{2}):(\d{2})(?:\.(\d{1,3}))?\]").expect("valid LRC timestamp regex"));
+// Timestamp kata A2: <01:23.45>kata
+static LRC_WORD_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<(\d{2}):(\d{2})(?:\.(\d{1,3}))?>").expect("valid LRC word regex"));
+
+// Tag HTML di subtitle (<b>, <i>, <font ...>) dan tag kata WebVTT (<00:01.000>, <c.red>)
+static SUBTITLE_TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"</?[^>]*>").expect("valid subtitle tag regex"));
+
+// Cari teks di lirik tanpa beda huruf besar/kecil; None kalau query kosong
+pub fn search_regex(query: &str) -> Option<Regex> {
+    if query.is_empty() {
+        return None;
+    }
+    Regex::new(&format!("(?i){}", regex::escape(query))).ok()
+}
+
+// Baris Ibrani / Arab: mayoritas karakter berarah kuat (L vs R / AL) adalah kanan-ke-kiri
+pub fn is_rtl(text: &str) -> bool {
+    let (mut rtl, mut ltr) = (0usize, 0usize);
+    for c in text.chars() {
+        match bidi_class(c) {
+            BidiClass::R | BidiClass::AL => rtl += 1,
+            BidiClass::L => ltr += 1,
+            _ => {}
+        }
+    }
+    rtl > ltr
+}
+
+// Urutan tampil (algoritma bidi Unicode), karena terminal menggambar sel dari kiri ke kanan
+pub fn visual_order(text: &str) -> Cow<'_, str> {
+    let info = BidiInfo::new(text, None);
+    match info.paragraphs.first() {
+        Some(para) => info.reorder_line(para, para.range.clone()),
+        None => Cow::Borrowed(text),
+    }
+}
+
+// `duration` untuk lirik tanpa timestamp; header LRC hanya ada di format LRC
+pub fn parse(source: &LyricsSource, duration: Duration) -> (Vec<LyricLine>, LrcMetadata) {
+    match source {
+        LyricsSource::Lrc(content) if is_lrc(content) => parse_lrc(content),
+        // Tag LYRICS / USLT sering berisi teks biasa tanpa timestamp
+        LyricsSource::Lrc(content) => (plain_lyrics(content, duration), LrcMetadata::default()),
+        LyricsSource::Srt(content) => (parse_srt(content), LrcMetadata::default()),
+        LyricsSource::Vtt(content) => (parse_vtt(content), LrcMetadata::default()),
+    }
+}
+
+// LRC = ada baris yang diawali timestamp [mm:ss] atau header [ar:...]
+pub fn is_lrc(content: &str) -> bool {
+    content.lines().map(str::trim).any(|line| LRC_TIME_RE.is_match(line) || is_lrc_metadata_line(line))
+}
+
+// Lirik tanpa timestamp: waktu tiap baris diperkirakan dengan membagi durasi lagu
+// sebanding panjang baris. Tidak akurat, tapi lirik tetap ikut bergulir.
+pub fn plain_lyrics(content: &str, duration: Duration) -> Vec<LyricLine> {
+    let lines: Vec<&str> = content.lines().map(str::trim).collect();
+    let total: usize = lines.iter().map(|l| l.chars().count()).sum();
+    let mut before = 0;
+    lines
+        .into_iter()
+        .map(|text| {
+            let time = if total > 0 { duration.mul_f64(before as f64 / total as f64) } else { Duration::ZERO };
+            before += text.chars().count();
+            LyricLine { time, text: text.to_string(), words: None }
+        })
+        .collect()
+}
+
+pub fn parse_lrc(content: &str) -> (Vec<LyricLine>, LrcMetadata) {
+    let mut lines = Vec::new();
+    let mut meta = LrcMetadata::default();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() { continue; }
+        if is_lrc_metadata_line(line) {
+            let (tag, value) = line[1..line.len() - 1].split_once(':').unwrap_or_default();
+            let value = value.trim();
+            if !value.is_empty() {
+                let value = Some(value.to_string());
+                match tag.to_ascii_lowercase().as_str() {
+                    "ar" => meta.artist = value,
+                    "ti" => meta.title = value,
+                    "al" => meta.album = value,
+                    "by" => meta.creator = value,
+                    _ => {}
+                }
+            }
+            continue;
+        }
+
+        let mut rest = line;
+        let mut times = Vec::new();
+        while let Some(cap) = LRC_TIME_RE.captures(rest) {
+            times.push(lrc_capture_time(&cap));
+            rest = rest[cap[0].len()..].trim_start();
+        }
+
+        // Baris campuran: yang punya <mm:ss.xx> dapat data per kata, sisanya teks biasa
+        let (text, words) = if LRC_WORD_RE.is_match(rest) {
+            let words = parse_lrc_words(rest, times.first().copied().unwrap_or_default());
+            let text = words.iter().map(|w| w.text.as_str()).collect::<String>().trim().to_string();
+            (text, Some(words))
+        } else {
+            (rest.trim().to_string(), None)
+        };
+        for time in times {
+            lines.push(LyricLine { time, text: text.clone(), words: words.clone() });
+        }
+    }
+    lines.sort_by_key(|k| k.time);
+    (lines, meta)
+}
+
+// Ubah capture (menit, detik, pecahan) jadi Duration; pecahan 1-3 digit
+fn lrc_capture_time(cap: &regex::Captures) -> Duration {
+    let min: u64 = cap[1].parse().unwrap_or(0);
+    let sec: u64 = cap[2].parse().unwrap_or(0);
+    let millis: u64 = if let Some(m) = cap.get(3) {
+        let m_str = m.as_str();
+        match m_str.len() {
+            1 => m_str.parse::<u64>().unwrap_or(0) * 100,
+            2 => m_str.parse::<u64>().unwrap_or(0) * 10,
+            _ => m_str.parse::<u64>().unwrap_or(0),
+        }
+    } else { 0 };
+    Duration::from_secs(min * 60 + sec) + Duration::from_millis(millis)
+}
+
+// Pecah "<01:23.45>word <01:23.90>by" jadi kata-kata bertimestamp.
+// Teks sebelum timestamp kata pertama memakai waktu baris.
+fn parse_lrc_words(body: &str, line_time: Duration) -> Vec<LyricWord> {
+    let mut words = Vec::new();
+    let mut start = line_time;
+    let mut last_end = 0;
+    for cap in LRC_WORD_RE.captures_iter(body) {
+        let Some(m) = cap.get(0) else { continue };
+        let text = &body[last_end..m.start()];
+        if !text.trim().is_empty() {
+            words.push(LyricWord { start, text: text.to_string() });
+        }
+        start = lrc_capture_time(&cap);
+        last_end = m.end();
+    }
+    let tail = &body[last_end..];
+    if !tail.trim().is_empty() {
+        words.push(LyricWord { start, text: tail.to_string() });
+    }
+    words
+}
+
+// SubRip: nomor urut, "00:01:02,500 --> 00:01:05,000", lalu teks sampai baris kosong
+pub fn parse_srt(content: &str) -> Vec<LyricLine> {
+    parse_cues(content, ',')
+}
+
+// WebVTT: seperti SRT tapi milidetik pakai titik, jam boleh tidak ditulis,
+// dan ada header WEBVTT / blok NOTE (tanpa "-->", jadi ikut terlewati)
+pub fn parse_vtt(content: &str) -> Vec<LyricLine> {
+    parse_cues(content, '.')
+}
+
+// Hanya waktu mulai tiap cue yang dipakai; teks multi-baris digabung jadi satu baris lirik
+fn parse_cues(content: &str, millis_sep: char) -> Vec<LyricLine> {
+    let mut lines = Vec::new();
+    let mut current: Option<(Duration, Vec<String>)> = None;
+    for line in content.lines().map(str::trim) {
+        if let Some((start, _)) = line.split_once("-->") {
+            if let Some(time) = parse_cue_time(start.trim(), millis_sep) {
+                push_cue(&mut lines, current.take());
+                current = Some((time, vec![]));
+            }
+        } else if line.is_empty() {
+            push_cue(&mut lines, current.take());
+        } else if let Some((_, text)) = &mut current {
+            let stripped = SUBTITLE_TAG_RE.replace_all(line, "");
+            if !stripped.trim().is_empty() {
+                text.push(stripped.trim().to_string());
+            }
+        }
+    }
+    push_cue(&mut lines, current);
+    lines.sort_by_key(|l| l.time);
+    lines
+}
+
+fn push_cue(lines: &mut Vec<LyricLine>, cue: Option<(Duration, Vec<String>)>) {
+    if let Some((time, text)) = cue {
+        lines.push(LyricLine { time, text: text.join(" "), words: None });
+    }
+}
+
+// "01:02:03,456" / "02:03.456" -> Duration
+fn parse_cue_time(stamp: &str, millis_sep: char) -> Option<Duration> {
+    let (clock, millis) = stamp.split_once(millis_sep)?;
+    let mut secs = 0u64;
+    for part in clock.split(':') {
+        secs = secs * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(Duration::from_secs(secs) + Duration::from_millis(millis.parse().ok()?))
+}
+
+// Kebalikan dari parse_lrc: [mm:ss.xx]teks, atau <mm:ss.xx>kata untuk baris A2
+pub fn format_lrc_line(line: &LyricLine) -> String {
+    let stamp = |t: Duration| format!("{:02}:{:02}.{:02}", t.as_secs() / 60, t.as_secs() % 60, t.subsec_millis() / 10);
+    let mut out = format!("[{}]", stamp(line.time));
+    match &line.words {
+        Some(words) => {
+            for word in words {
+                out.push_str(&format!("<{}>{}", stamp(word.start), word.text));
+            }
+        }
+        None => out.push_str(&line.text),
+    }
+    out
+}
+
+// Header LRC seperti [ar:Artist], [ti:Title], [offset:+100]
+fn is_lrc_metadata_line(line: &str) -> bool {
+    let Some(inner) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) else {
+        return false;
+    };
+    match inner.split_once(':') {
+        Some((tag, _)) => !tag.is_empty() && tag.chars().all(|c| c.is_ascii_alphabetic()),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_lrc_has_no_lines_or_metadata() {
+        let (lines, meta) = parse_lrc("");
+        assert!(lines.is_empty());
+        assert!(meta.artist.is_none() && meta.title.is_none() && meta.album.is_none() && meta.creator.is_none());
+    }
+
+    #[test]
+    fn every_leading_timestamp_becomes_a_line() {
+        let cases: &[(&str, &[(u64, &str)])] = &[
+            ("[01:23.45]x", &[(83_450, "x")]),
+            ("[01:23.45][02:34.56]x", &[(83_450, "x"), (154_560, "x")]),
+            ("[00:10][00:20.5][00:30.123] Chorus", &[(10_000, "Chorus"), (20_500, "Chorus"), (30_123, "Chorus")]),
+            // Urut waktu, bukan urut baris
+            ("[02:00.00]b\n[01:00.00][03:00.00]a", &[(60_000, "a"), (120_000, "b"), (180_000, "a")]),
+        ];
+        for (input, expected) in cases {
+            let (lines, _) = parse_lrc(input);
+            let got: Vec<(u64, &str)> = lines.iter().map(|l| (l.time.as_millis() as u64, l.text.as_str())).collect();
+            assert_eq!(&got, expected, "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn header_only_lrc_has_metadata_but_no_lines() {
+        let (lines, meta) = parse_lrc("[ar:Some Artist]\n[ti:Some Title]\n[al: Album ]\n[by:]\n[offset:+100]");
+        assert!(lines.is_empty());
+        assert_eq!(meta.artist.as_deref(), Some("Some Artist"));
+        assert_eq!(meta.title.as_deref(), Some("Some Title"));
+        assert_eq!(meta.album.as_deref(), Some("Album"));
+        // Header kosong tidak dianggap ada
+        assert_eq!(meta.creator, None);
+        assert!(is_lrc("[ti:Some Title]"));
+    }
+
+    #[test]
+    fn mixed_header_and_lyrics() {
+        let (lines, meta) = parse_lrc("[ti:Song]\n[by:someone]\n[00:01.00]first\n[ar:Late Header]\n[00:02.00]second");
+        let texts: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, ["first", "second"]);
+        assert_eq!(meta.title.as_deref(), Some("Song"));
+        assert_eq!(meta.artist.as_deref(), Some("Late Header"));
+        assert_eq!(meta.creator.as_deref(), Some("someone"));
+        assert_eq!(meta.album, None);
+    }
+
+    #[test]
+    fn empty_plain_lyrics_has_no_lines() {
+        assert!(plain_lyrics("", Duration::from_secs(180)).is_empty());
+    }
+}
@@ -1,42 +1,464 @@
 use anyhow::{Context, Result};
+use base64::prelude::*;
+use clap::Parser;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
+    event::{
+        self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle},
 };
-use image::ImageReader;
+use image::ImageFormat;
 use lofty::prelude::*;
+use lofty::config::WriteOptions;
 use lofty::probe::Probe;
+use lofty::file::FileType;
+use lofty::config::ParseOptions;
+use lofty::id3::v2::{Frame as Id3v2Frame, Id3v2Tag, PopularimeterFrame};
+use lofty::mpeg::MpegFile;
+use lofty::tag::{ItemKey, ItemValue, Tag, TagType};
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Gauge, List, ListItem, ListState, Padding, Paragraph, Wrap},
+    widgets::{Block, BorderType, Borders, Clear, Gauge, LineGauge, List, ListItem, ListState, Padding, Paragraph, Tabs, Wrap},
 };
 use ratatui_image::{
     picker::Picker,
     protocol::StatefulProtocol,
     Resize, StatefulImage,
 };
-use regex::Regex;
+use rand::seq::SliceRandom;
+use rayon::prelude::*;
+use rodio::buffer::SamplesBuffer;
+use rodio::decoder::DecoderError;
 use rodio::{Decoder, OutputStream, Sink, Source};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::fs::{self, File};
-use std::io::{self, stdout, BufReader, Cursor};
+use std::io::{self, stdout, BufReader, IsTerminal, Read, Write};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+use unicode_normalization::UnicodeNormalization;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-// --- KONFIGURASI FOLDER MUSIK ---
-const MUSIC_DIR: &str = "/home/naaklaam/Music";
+mod browser;
+mod buffer;
+mod cache;
+mod cli;
+mod config;
+mod cue;
+mod device;
+mod dirs;
+mod duplicates;
+mod headless;
+mod layout;
+mod library;
+mod logging;
+mod lyrics;
+#[cfg(feature = "mpris")]
+mod mpris;
+#[cfg(feature = "notify")]
+mod notify;
+mod position;
+mod remote;
+mod search;
+mod sort;
+mod store;
+mod session;
+mod theme;
+mod thumb_cache;
+mod verify;
+mod watch;
+mod worker;
+use browser::DirTree;
+use buffer::BufferedSource;
+use config::{BorderStyle, Config, StartupAction};
+use cue::CueTrack;
+use lyrics::{format_lrc_line, LrcMetadata, LyricLine};
+use library::{collection_stats, spawn_meta_scan, CollectionStats, MetaCache};
+use position::PositionTracker;
+use search::{fuzzy_match, fuzzy_score};
+use sort::{natural_sort_key, NaturalKey};
+use theme::Theme;
+use watch::{LibraryChange, LibraryWatcher};
+use worker::{ReplayGain, TrackMetadata, WorkerMsg, WorkerReply};
 
-// Struktur data lirik
-#[derive(Clone)]
-struct LyricLine {
-    time: Duration,
-    text: String,
+// Scroll lirik manual: lompatan PageUp/PageDown & batas waktu sebelum auto-sync lagi
+const LYRICS_PAGE: isize = 5;
+const LYRICS_MANUAL_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Nama file cover di folder album, urut prioritas
+const FOLDER_COVER_NAMES: &[&str] = &["folder.jpg", "folder.png", "cover.jpg", "cover.png", "front.jpg", "album.jpg"];
+
+// Ekstensi file yang dianggap lagu
+const AUDIO_EXTENSIONS: &[&str] = &[
+    "flac",
+    "mp3",
+    "wav",
+    "ogg",
+    "opus",
+    "m4a",
+    // Monkey's Audio: tag terbaca lewat Lofty, tapi belum ada decoder-nya
+    #[cfg(feature = "ape-support")]
+    "ape",
+];
+// Jumlah lagu yang diingat untuk tombol `p`
+const HISTORY_LIMIT: usize = 50;
+
+// Fade play/pause: 10 langkah x 20 ms = 200 ms
+const FADE_STEPS: u32 = 10;
+const FADE_STEP_MS: u64 = 20;
+
+// Judul panjang di panel Info bergeser satu karakter tiap 500 ms; jarak antar ulangan
+const TITLE_SCROLL_STEP: Duration = Duration::from_millis(500);
+const TITLE_SCROLL_GAP: &str = "   ";
+
+// Terminal lebih sempit dari ini: selalu mini player
+const MINI_PLAYER_BELOW: u16 = 60;
+
+// Kelompok baris di overlay `?` dan --print-keybindings, urut seperti di enum
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum KeybindingSection {
+    Playback,
+    Navigation,
+    Library,
+    View,
+    Other,
+}
+
+impl KeybindingSection {
+    const ALL: [KeybindingSection; 5] = [
+        KeybindingSection::Playback,
+        KeybindingSection::Navigation,
+        KeybindingSection::Library,
+        KeybindingSection::View,
+        KeybindingSection::Other,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            KeybindingSection::Playback => "Playback",
+            KeybindingSection::Navigation => "Navigation",
+            KeybindingSection::Library => "Library",
+            KeybindingSection::View => "View",
+            KeybindingSection::Other => "Other",
+        }
+    }
+}
+
+// Isi overlay bantuan `?` & --print-keybindings: tombol mode normal -> keterangan.
+// Tombol baru cukup ditambah di sini.
+const KEYBINDINGS: &[(KeybindingSection, &str, &str)] = &[
+    (KeybindingSection::Playback, "Space", "Play / pause"),
+    (KeybindingSection::Playback, "n / N", "Next / previous track"),
+    (KeybindingSection::Playback, "p / Backspace", "Back to previously played track"),
+    (KeybindingSection::Playback, "← / →", "Seek 5 s (Shift: 30 s)"),
+    (KeybindingSection::Playback, "{ / }", "Previous / next chapter"),
+    (KeybindingSection::Playback, "[ / ]", "Volume down / up"),
+    (KeybindingSection::Playback, "< / >", "Playback speed down / up"),
+    (KeybindingSection::Playback, "s", "Shuffle on / off"),
+    (KeybindingSection::Playback, "r", "Repeat: off / all / one"),
+    (KeybindingSection::Playback, "R / Ctrl+R", "ReplayGain on / off, track / album"),
+    (KeybindingSection::Playback, "0 - 5", "Rate current track"),
+    (KeybindingSection::Playback, "t", "Sleep timer"),
+    (KeybindingSection::Navigation, "j / k, ↑ / ↓", "Move cursor"),
+    (KeybindingSection::Navigation, "Enter", "Play selected file / open playlist"),
+    (KeybindingSection::Navigation, "/", "Search file list (lyrics when focused)"),
+    (KeybindingSection::Navigation, "Tab / Q", "Switch panel"),
+    (KeybindingSection::Navigation, "v", "Folder tree view"),
+    (KeybindingSection::Navigation, "h / l", "Collapse / open folder (tree)"),
+    (KeybindingSection::Navigation, "o", "Cycle sort order"),
+    (KeybindingSection::Navigation, "PgUp / PgDn, Home / End", "Scroll lyrics"),
+    (KeybindingSection::Library, "a / A", "Add to queue / play next"),
+    (KeybindingSection::Library, "d", "Remove from queue (queue panel)"),
+    (KeybindingSection::Library, "Ctrl+↑ / ↓, Ctrl+Home / End", "Move queue entry (queue panel)"),
+    (KeybindingSection::Library, "b / B", "Bookmark file / bookmarks"),
+    (KeybindingSection::Library, "P", "Virtual playlists"),
+    (KeybindingSection::Library, "Ctrl+T / Ctrl+W", "New tab / close tab"),
+    (KeybindingSection::Library, "Alt+1..9", "Switch tab"),
+    (KeybindingSection::Library, "M", "Move file to next tab"),
+    (KeybindingSection::Library, "i", "All tag fields"),
+    (KeybindingSection::Library, "e", "Edit tags"),
+    (KeybindingSection::Library, "W", "Save cover art as PNG"),
+    (KeybindingSection::Library, "x", "Export lyrics to .lrc"),
+    (KeybindingSection::Library, "S", "Library statistics"),
+    (KeybindingSection::View, "K", "Karaoke view"),
+    (KeybindingSection::View, "c", "Full-screen cover art"),
+    (KeybindingSection::View, "m", "Mini player"),
+    (KeybindingSection::View, "H", "High contrast"),
+    (KeybindingSection::View, "Ctrl+A", "Accessibility mode (patterns instead of colors)"),
+    (KeybindingSection::View, "D", "Audio output device"),
+    (KeybindingSection::Other, ":", "Command prompt (Tab completes)"),
+    (KeybindingSection::Other, "?", "This help"),
+    (KeybindingSection::Other, "q / Ctrl+C", "Quit"),
+];
+
+// Perintah `:` untuk autocomplete: nama, argumen & keterangan di popup
+const COMMANDS: &[(&str, &str, &str)] = &[
+    ("w", "<path>", "save file list as M3U8"),
+    ("sort", "<mode>", "name, track, modified, duration, rating, plays"),
+    ("theme", "<name>", "switch color theme"),
+    ("device", "<name>", "switch audio output"),
+    ("goto", "<m:ss>", "seek to position (also h:mm:ss / secs, .5 fractions)"),
+    ("seek", "<±time>", "like goto; +30 / -1:00 seek relative"),
+    ("t", "<min>", "sleep timer (0 cancels)"),
+    ("q", "", "quit"),
+];
+
+// Overlay "Volume: 80%" setelah scroll mouse, hilang sendiri
+const VOLUME_OVERLAY_TIMEOUT: Duration = Duration::from_millis(1500);
+const LIBRARY_FLASH_TIMEOUT: Duration = Duration::from_millis(2000); // "Library updated" di judul file list
+
+// UI digambar lebih jarang saat terminal tidak fokus, atau lagu sudah lama di-pause tanpa input
+const UNFOCUSED_RENDER_INTERVAL: Duration = Duration::from_millis(500);
+const IDLE_RENDER_INTERVAL: Duration = Duration::from_millis(1000);
+const IDLE_AFTER: Duration = Duration::from_secs(5);
+
+// Sisa waktu lagu saat lagu berikutnya mulai di-decode (gapless)
+const PRELOAD_BEFORE_END: Duration = Duration::from_secs(3);
+
+// File JSON di $XDG_DATA_HOME/punini
+const BOOKMARKS_FILE: &str = "bookmarks.json";
+const RATINGS_FILE: &str = "ratings.json";
+const PLAYCOUNTS_FILE: &str = "playcounts.json";
+const PLAY_COUNT_AFTER: Duration = Duration::from_secs(30); // Lagu dihitung "diputar" setelah 30 detik
+const VIRTUAL_PLAYLIST_LEN: usize = 50; // Panjang default "recently added" / "most played"
+
+// Jumlah maksimal hasil pencarian `/` yang ditampilkan
+const SEARCH_MAX_RESULTS: usize = 100;
+
+// Ekstensi file playlist (ikut tampil di browser)
+const PLAYLIST_EXTENSIONS: &[&str] = &["m3u", "m3u8"];
+
+// Chapter di dalam satu file (mis. dari tag CHAPTERxxx VorbisComment)
+#[derive(Clone, Debug)]
+struct Chapter {
+    start: Duration,
+    title: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RepeatMode {
+    Off,
+    All,
+    One,
+}
+
+impl RepeatMode {
+    fn next(self) -> Self {
+        match self {
+            RepeatMode::Off => RepeatMode::All,
+            RepeatMode::All => RepeatMode::One,
+            RepeatMode::One => RepeatMode::Off,
+        }
+    }
+}
+
+// Sumber gain ReplayGain: per lagu, atau per album (dinamika antar lagu album tetap)
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RgMode {
+    Track,
+    Album,
+}
+
+impl RgMode {
+    fn toggle(self) -> Self {
+        match self {
+            RgMode::Track => RgMode::Album,
+            RgMode::Album => RgMode::Track,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            RgMode::Track => "track",
+            RgMode::Album => "album",
+        }
+    }
+}
+
+// Panel yang sedang aktif di sisi kiri
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Focus {
+    Browser,
+    Queue,
+    Tree, // Panel pohon folder di tampilan dua panel
+    Lyrics,
+}
+
+// Urutan file list, diganti dengan tombol `o`
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SortMode {
+    Name,
+    TrackNumber,
+    Modified,
+    Duration,
+    Rating,
+    Plays,
+}
+
+impl SortMode {
+    fn next(self) -> Self {
+        match self {
+            SortMode::Name => SortMode::TrackNumber,
+            SortMode::TrackNumber => SortMode::Modified,
+            SortMode::Modified => SortMode::Duration,
+            SortMode::Duration => SortMode::Rating,
+            SortMode::Rating => SortMode::Plays,
+            SortMode::Plays => SortMode::Name,
+        }
+    }
+
+    // Nama untuk --sort=<mode>
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(SortMode::Name),
+            "track" => Some(SortMode::TrackNumber),
+            "modified" => Some(SortMode::Modified),
+            "duration" => Some(SortMode::Duration),
+            "rating" => Some(SortMode::Rating),
+            "plays" => Some(SortMode::Plays),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortMode::Name => "Name",
+            SortMode::TrackNumber => "Track #",
+            SortMode::Modified => "Modified",
+            SortMode::Duration => "Duration",
+            SortMode::Rating => "Rating",
+            SortMode::Plays => "Plays",
+        }
+    }
+}
+
+// Tab file list (Ctrl+T). Isi tab aktif ada langsung di AppState (`files`, `file_list_state`, ...);
+// di sini hanya tersimpan selama tabnya tidak aktif.
+#[derive(Default)]
+struct PlaylistTab {
+    name: String,
+    files: Vec<PathBuf>,
+    file_list_state: ListState,
+    display_names: HashMap<usize, String>,
+    file_cue: HashMap<usize, usize>,
+    shuffle_order: Vec<usize>,
+    virtual_playlist: Option<VirtualPlaylist>,
+    files_dir: Option<PathBuf>,
+}
+
+impl PlaylistTab {
+    fn new(name: String) -> Self {
+        Self { name, ..Default::default() }
+    }
+}
+
+// Playlist virtual (`P`): dibangun dari seluruh library, bukan folder yang sedang dibuka
+#[derive(Clone, Debug, PartialEq)]
+enum VirtualPlaylist {
+    RecentlyAdded(usize),
+    MostPlayed(usize),
+    Unplayed,
+    ByGenre(String),
+    // File .m3u yang dibuka dari file list; urutannya urutan yang ditulis di file
+    File(PathBuf),
+}
+
+impl VirtualPlaylist {
+    fn label(&self) -> String {
+        match self {
+            VirtualPlaylist::RecentlyAdded(n) => format!("Recently added ({})", n),
+            VirtualPlaylist::MostPlayed(n) => format!("Most played ({})", n),
+            VirtualPlaylist::Unplayed => "Unplayed".to_string(),
+            VirtualPlaylist::ByGenre(genre) => format!("Genre: {}", genre),
+            VirtualPlaylist::File(path) => display_file_name(path),
+        }
+    }
+}
+
+// Field yang bisa diedit dengan `e` di panel Info
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TagField {
+    Title,
+    Artist,
+    Album,
+    Year,
+}
+
+impl TagField {
+    fn next(self) -> Self {
+        match self {
+            TagField::Title => TagField::Artist,
+            TagField::Artist => TagField::Album,
+            TagField::Album => TagField::Year,
+            TagField::Year => TagField::Title,
+        }
+    }
+
+    fn prev(self) -> Self {
+        self.next().next().next()
+    }
+}
+
+// Tema aksesibilitas: hanya pakai Modifier & karakter, tanpa field Color
+struct AccessibilityTheme {
+    file_highlight: Style,
+    lyric_highlight: Style,
+    border_playing: BorderType,
+    border_idle: BorderType,
+    gauge_filled: char,
+    gauge_empty: char,
+}
+
+impl Default for AccessibilityTheme {
+    fn default() -> Self {
+        Self {
+            file_highlight: Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            lyric_highlight: Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            border_playing: BorderType::Thick,
+            border_idle: BorderType::Plain,
+            gauge_filled: '▓',
+            gauge_empty: '░',
+        }
+    }
 }
 
 struct AppState {
+    config: Config,
+
     // --- Player System ---
-    sink: Sink,
+    sink: Arc<Sink>, // Arc supaya thread fade bisa ikut mengatur volume
+    position_tracker: PositionTracker, // Posisi untuk UI, lebih stabil dari sink.get_pos()
     _stream: OutputStream,
+    device_name: Option<String>,   // Output yang sedang dipakai (None = tidak diketahui)
+    current_path: Option<PathBuf>, // Lagu yang sedang diputar
+    volume: f32,                   // 0.0 - 1.5, disinkronkan ke sink
+    shuffle: bool,
+    shuffle_order: Vec<usize>,     // Permutasi index `files` saat shuffle aktif
+    history: Vec<PathBuf>,         // Lagu yang sudah diputar, terbaru di akhir
+    repeat: RepeatMode,
+    track_was_loaded: bool,        // Cegah auto-advance sebelum ada lagu yang pernah diputar
+    current_format: Option<(u16, u32)>, // (channels, sample rate) lagu sekarang
+    play_counted: bool,            // Lagu sekarang sudah masuk play_counts
+    speed: f32,                    // `<` / `>`: tempo 0.5x - 2.0x (pitch ikut berubah)
+    rg_enabled: bool,              // `R`: normalisasi volume dengan tag ReplayGain
+    rg_mode: RgMode,               // Ctrl+R: gain track / album
+    replaygain: ReplayGain,        // Tag ReplayGain lagu sekarang
+    fading: bool,                  // Fade play/pause sedang jalan, Space diabaikan
+    fade_task: Option<JoinHandle<()>>,
+
+    // --- Gapless ---
+    preload_task: Option<JoinHandle<Option<SamplesBuffer<i16>>>>, // Decode lagu berikutnya
+    preload_checked: bool,        // Sudah diputuskan preload / tidak untuk lagu ini
+    preload_target: Option<PathBuf>, // Lagu yang di-preload (task jalan atau sudah di-append)
+    preload_queued: bool,         // SamplesBuffer sudah ada di antrian sink
+    preload_duration: Duration,
 
     // --- Track Metadata ---
     title: String,
@@ -44,19 +466,187 @@ struct AppState {
     album: String,
     duration: Duration,
     cover_art: Option<Box<dyn StatefulProtocol>>,
+    cover_data: Option<Vec<u8>>,       // Bytes cover asli (ukuran penuh), untuk disimpan dengan `W`
+
+    chapters: Vec<Chapter>,
+    year: Option<u32>,
+    genre: Option<String>,
+    composer: Option<String>,
+    comment: Option<String>,
+    tag_fields: Vec<(String, String)>, // Semua field tag, untuk popup `i`
+    bitrate: Option<u32>,     // kbps
+    sample_rate: Option<u32>, // Hz
+    channels: Option<u8>,
+    file_size: u64,           // Byte, 0 = tidak diketahui
+    playback_unsupported: bool, // Format-nya tidak dikenal decoder (mis. APE, Opus)
+
+    // --- Metadata Worker ---
+    worker_tx: Sender<WorkerMsg>,
+    worker_rx: Receiver<WorkerReply>,
+    meta_ready: bool, // Balasan worker untuk lagu sekarang sudah diterapkan
+    headless: bool,   // Tanpa TUI: cover tidak dirender
 
     // --- Lyrics System ---
     lyrics: Vec<LyricLine>,
     lyrics_state: ListState,
+    lyrics_manual_scroll: bool,     // Auto-sync dimatikan sementara
+    lyrics_query: Option<String>,   // Pencarian `/` di panel lirik; Some selama hasilnya masih disorot
+    lyrics_last_scroll: Instant,    // Kapan terakhir user scroll manual
 
     // --- File Browser System ---
     files: Vec<PathBuf>,      // Daftar file audio yang ditemukan
     file_list_state: ListState, // Posisi kursor di daftar file
+    display_names: HashMap<usize, String>, // Nama dari #EXTINF playlist, per index `files`
+    bookmarks: Vec<PathBuf>, // Lagu favorit, disimpan ke bookmarks.json
+    ratings: HashMap<PathBuf, u8>, // Rating 1-5 per lagu, disimpan ke ratings.json
+    play_counts: HashMap<PathBuf, u32>, // Berapa kali lagu diputar, disimpan ke playcounts.json
+    bookmarks_state: ListState,
+    library: Vec<PathBuf>,                     // Semua file hasil scan music_dir, sumber playlist virtual
+    virtual_playlist: Option<VirtualPlaylist>, // Playlist virtual yang sedang tampil di file list
+    files_dir: Option<PathBuf>, // Folder yang hasil scan-nya tampil di file list; None = playlist / daftar yang diubah `M`
+    playlists_state: ListState,                // Kursor menu `P`
+    stats_cache: Option<CollectionStats>,      // Statistik `S`, dihitung saat pertama dibuka
+    watcher: Option<LibraryWatcher>,           // Live update dari folder musik (hanya TUI)
+    library_flash: Option<Instant>,            // Kapan file list terakhir berubah karena watcher
+    tree_dir: Option<PathBuf>,                 // Folder yang isinya tampil di panel file (tampilan dua panel)
+    devices: Vec<String>,                      // Isi menu `D`, dibaca ulang setiap dibuka
+    devices_state: ListState,
+    tree: DirTree,   // Pohon folder musik untuk tampilan dua panel
+    tree_view: bool, // `v`: panel kiri = pohon folder + isi folder yang dipilih
+    file_cue: HashMap<usize, usize>,       // Index `files` -> nomor lagu CUE (file yang sama muncul berkali-kali)
+    cue_tracks: HashMap<PathBuf, Vec<CueTrack>>, // Lagu virtual per file audio yang punya .cue
+    current_cue: Option<usize>,            // Lagu CUE yang sedang diputar, dihitung dari posisi
+    sort_mode: SortMode,
+    meta_cache: MetaCache,      // Diisi thread background, '?' selama belum ada
+    focus: Focus,               // Panel kiri: browser atau queue
+    queue: VecDeque<PathBuf>,   // Lagu yang diputar berikutnya, sebelum lanjut ke library
+    queue_state: ListState,
+    filtered_files: Vec<usize>, // Hasil pencarian `/` (index ke `files`, urut skor), kursor bergerak di sini
+
+    // --- Status Bar ---
+    last_error: Option<String>,
+    status: Option<String>,          // Pesan info (bukan error)
+    confirm: Option<ConfirmAction>,  // Pertanyaan y/n yang sedang ditampilkan
+
+    mode: AppMode,
+    command_completion: usize, // Completion yang dipilih (Tab) di prompt `:`
+    display_mode: DisplayMode, // Layout yang digambar (biasa / karaoke / cover)
+    high_contrast: bool,       // Warna kontras tinggi (tombol H), disimpan di config
+    theme: Theme,              // Skema warna dari [theme] / --theme
+
+    tabs: Vec<PlaylistTab>,     // Minimal satu; tabs[active_tab] kosong, isinya sedang dipakai di `files`
+    active_tab: usize,
+    playing_tab: Option<usize>, // Tab asal lagu yang sedang diputar (tanda ▶), None kalau tabnya ditutup
+    sleep_timer: Option<Instant>, // Pause otomatis saat deadline lewat, tetap jalan walau ganti lagu
+    window_title: Option<String>, // Judul terminal terakhir yang di-set
+    progress_area: Rect,          // Posisi progress bar di layar terakhir, untuk klik mouse
+    player_area: Rect,            // Panel kanan (cover, info, lirik, progress), untuk scroll volume
+    volume_overlay: Option<Instant>, // Kapan volume terakhir diubah lewat scroll
+    title_scroll_offset: usize,      // Marquee judul di panel Info (karakter)
+    title_scroll_at: Instant,        // Kapan marquee terakhir bergeser
+    shutdown: Arc<AtomicBool>,    // Di-set handler SIGTERM/SIGINT
+    remote: Option<remote::Remote>,
+
+    #[cfg(feature = "mpris")]
+    mpris: Option<mpris::Mpris>,
+    #[cfg(feature = "mpris")]
+    mpris_path: Option<PathBuf>, // Lagu terakhir yang dilaporkan ke D-Bus
+    #[cfg(feature = "notify")]
+    notification_handle: Option<notify_rust::NotificationHandle>,
+}
+
+// Mode input keyboard
+#[derive(Debug, PartialEq)]
+enum AppMode {
+    Normal,
+    // Prompt `:` di status bar, berisi teks yang sedang diketik
+    Command(String),
+    // Pencarian `/` di panel file list, berisi query
+    Search(String),
+    // Mengetik query pencarian lirik (isi di `lyrics_query`)
+    LyricsSearch,
+    // Popup `i` berisi semua field tag, dengan offset scroll
+    TagInfo(u16),
+    // Overlay daftar tombol `?`, dengan offset scroll
+    Help(u16),
+    // Overlay bookmark `B`
+    Bookmarks,
+    // Menu playlist virtual `P`
+    VirtualPlaylists,
+    // Layar statistik library `S`, ditutup dengan tombol apa saja
+    Stats,
+    // Menu output audio `D`
+    Devices,
+    // Edit tag `e`: field yang dipilih, isi input, posisi kursor (index karakter)
+    TagEdit { field: TagField, input: String, cursor: usize },
+}
+
+// Tampilan layar penuh, terpisah dari mode input: tombol biasa tetap jalan
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DisplayMode {
+    Normal,
+    // `K`: lirik saja, baris aktif di tengah layar
+    Karaoke,
+    // `c`: cover art memenuhi layar
+    Cover,
+    // `m` (otomatis kalau terminal < 60 kolom): satu baris judul + progress
+    Mini,
+}
+
+// Aksi yang menunggu konfirmasi y/n di status bar
+#[derive(Debug)]
+enum ConfirmAction {
+    OverwriteLrc(PathBuf),
+}
+
+// Sink, stream & protocol gambar tidak punya Debug, jadi ditulis manual
+impl fmt::Debug for AppState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppState")
+            .field("config", &self.config)
+            .field("sink", &format_args!("<Rodio Sink>"))
+            .field("current_path", &self.current_path)
+            .field("volume", &self.volume)
+            .field("shuffle", &self.shuffle)
+            .field("history", &format_args!("<{} tracks>", self.history.len()))
+            .field("repeat", &self.repeat)
+            .field("title", &self.title)
+            .field("artist", &self.artist)
+            .field("album", &self.album)
+            .field("duration", &self.duration)
+            .field("chapters", &self.chapters)
+            .field("cover_art", &self.cover_art.as_ref().map(|_| format_args!("<image>")))
+            .field("lyrics", &format_args!("<{} lines>", self.lyrics.len()))
+            .field("lyrics_state", &self.lyrics_state)
+            .field("lyrics_manual_scroll", &self.lyrics_manual_scroll)
+            .field("files", &format_args!("<{} files>", self.files.len()))
+            .field("file_list_state", &self.file_list_state)
+            .field("display_names", &format_args!("<{} names>", self.display_names.len()))
+            .field("sort_mode", &self.sort_mode)
+            .field("focus", &self.focus)
+            .field("queue", &self.queue)
+            .field("queue_state", &self.queue_state)
+            .field("filtered_files", &format_args!("<{} matches>", self.filtered_files.len()))
+            .field("last_error", &self.last_error)
+            .field("status", &self.status)
+            .field("confirm", &self.confirm)
+            .field("mode", &self.mode)
+            .field("display_mode", &self.display_mode)
+            .field("high_contrast", &self.high_contrast)
+            .field("theme", &self.theme)
+            .field("tabs", &format_args!("<{} tabs>", self.tabs.len()))
+            .field("active_tab", &self.active_tab)
+            .field("playing_tab", &self.playing_tab)
+            .field("sleep_timer", &self.sleep_timer)
+            .finish_non_exhaustive()
+    }
 }
 
 impl AppState {
     // Fungsi untuk memuat lagu baru ke dalam state
     fn load_track(&mut self, path: &Path) {
+        self.finish_fade();
+
         // 1. Stop track sebelumnya (jika ada)
         if !self.sink.empty() {
             self.sink.stop();
@@ -70,320 +660,4819 @@ impl AppState {
             // Sink akan terus kita pakai.
         }
 
-        // Reset Metadata Visual
-        self.title = "Loading...".to_string();
-        self.artist = "-".to_string();
-        self.album = "-".to_string();
-        self.cover_art = None;
-        self.lyrics = vec![];
-        self.duration = Duration::from_secs(0);
+        self.begin_track(path);
+        self.track_was_loaded = false;
+        self.current_format = None;
+        self.playing_tab = Some(self.active_tab);
 
         // 2. Baca Audio File
-        let file_res = File::open(path);
-        if let Ok(file) = file_res {
-            let reader = BufReader::new(file);
-            if let Ok(source) = Decoder::new(reader) {
+        let source = File::open(path).map_err(|e| e.to_string()).and_then(|file| {
+            Decoder::new(BufReader::new(file)).map_err(|e| {
+                // Mis. APE / Opus: tag terbaca, tapi symphonia belum punya codec-nya
+                self.playback_unsupported = matches!(e, DecoderError::UnrecognizedFormat);
+                e.to_string()
+            })
+        });
+        match source {
+            Err(e) => {
+                warn!("cannot play {}: {}", path.display(), e);
+                self.last_error = Some(format!("Cannot play {}: {}", display_file_name(path), e));
+            }
+            Ok(source) => {
                 self.duration = source.total_duration().unwrap_or(Duration::from_secs(0));
+                self.current_format = Some((source.channels(), source.sample_rate()));
 
                 // Hack untuk Rodio: Buat Sink baru setiap ganti lagu adalah cara paling aman
                 // untuk menghindari suara menumpuk, tapi sink butuh stream_handle.
                 // Disini kita pakai `sink.append` tapi sebelumnya kita `sink.stop()`.
                 // Perilaku `stop` rodio adalah mengosongkan queue.
+                // Decoding dipindah ke thread sendiri supaya draw loop tidak bikin audio patah-patah
                 self.sink.stop();
-                self.sink.append(source);
+                self.append_source(path, source);
                 self.sink.play();
+                self.track_was_loaded = true;
             }
         }
+    }
 
-        // 3. Baca Metadata (Lofty)
-        if let Ok(tagged_file) = Probe::open(path).and_then(|p| p.read()) {
-            if let Some(t) = tagged_file.primary_tag() {
-                self.title = t.title().as_deref().unwrap_or("Unknown Title").to_string();
-                self.artist = t.artist().as_deref().unwrap_or("Unknown Artist").to_string();
-                self.album = t.album().as_deref().unwrap_or("Unknown Album").to_string();
-
-                // Cover Art
-                if let Some(pic) = t.pictures().first() {
-                    if let Ok(mut picker) = Picker::from_termios() {
-                         let img_reader = ImageReader::new(Cursor::new(pic.data()));
-                         if let Ok(reader) = img_reader.with_guessed_format() {
-                             if let Ok(decoded) = reader.decode() {
-                                 self.cover_art = Some(picker.new_resize_protocol(decoded));
-                             }
-                         }
-                    }
-                }
-
-                // Lyrics
-                let lrc_path = path.with_extension("lrc");
-                if lrc_path.exists() {
-                     if let Ok(content) = fs::read_to_string(lrc_path) {
-                         self.lyrics = parse_lrc(&content);
-                     }
-                } else {
-                    // Embedded Lyrics check
-                    for item in t.items() {
-                        if item.key() == &lofty::tag::ItemKey::Lyrics {
-                            if let lofty::tag::ItemValue::Text(text) = item.value() {
-                                self.lyrics = parse_lrc(text);
-                                break;
-                            }
-                        }
-                    }
+    // Lewat thread decode; kalau thread gagal dibuat, file dibuka ulang dan decoder langsung ke sink
+    fn append_source(&self, path: &Path, source: Decoder<BufReader<File>>) {
+        match BufferedSource::spawn(source, self.config.prebuffer_secs) {
+            Ok(buffered) => self.sink.append(buffered.speed(self.speed)),
+            Err(e) => {
+                warn!("cannot spawn decode thread for {}: {}; playing unbuffered", path.display(), e);
+                let plain = File::open(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|file| Decoder::new(BufReader::new(file)).map_err(|e| e.to_string()));
+                match plain {
+                    Ok(plain) => self.sink.append(plain.speed(self.speed)),
+                    Err(e) => warn!("cannot reopen {}: {}", path.display(), e),
                 }
             }
+        }
+    }
+
+    // Play/pause dengan fade singkat, bukan berhenti mendadak
+    fn toggle_pause(&mut self) {
+        if self.fading {
+            return;
+        }
+        self.fading = true;
+        self.fade_task = Some(if self.sink.is_paused() {
+            fade_in(&self.sink, self.output_volume(), FADE_STEPS, FADE_STEP_MS)
         } else {
-            // Jika gagal baca tag, pakai nama file
-            self.title = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            fade_out(&self.sink, FADE_STEPS, FADE_STEP_MS)
+        });
+    }
+
+    // Dipanggil tiap tick: fade sudah selesai?
+    fn tick_fade(&mut self) {
+        if self.fade_task.as_ref().is_some_and(|task| task.is_finished()) {
+            self.fade_task = None;
+            self.fading = false;
         }
     }
-}
 
-fn main() -> Result<()> {
-    // 1. Setup Audio
-    let (_stream, stream_handle) = OutputStream::try_default().context("No audio device")?;
-    let sink = Sink::try_new(&stream_handle).context("Failed to create sink")?;
+    // Tunggu fade selesai (maks. 200 ms) supaya pause dari fade_out tidak kena lagu baru
+    fn finish_fade(&mut self) {
+        if let Some(task) = self.fade_task.take() {
+            let _ = task.join();
+        }
+        self.fading = false;
+    }
 
-    // 2. Scan Folder Musik
-    let music_path = Path::new(MUSIC_DIR);
-    let mut files = Vec::new();
-    if music_path.exists() {
-        if let Ok(entries) = fs::read_dir(music_path) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_file() {
-                    if let Some(ext) = path.extension() {
-                        let ext_str = ext.to_string_lossy().to_lowercase();
-                        if ["flac", "mp3", "wav", "ogg", "m4a"].contains(&ext_str.as_str()) {
-                            files.push(path);
-                        }
+    // Proses perintah D-Bus & laporkan state terbaru ke MPRIS
+    #[cfg(feature = "mpris")]
+    fn tick_mpris(&mut self) {
+        use mpris::MprisCommand;
+
+        let Some(m) = &self.mpris else { return };
+        let commands: Vec<MprisCommand> = m.commands.try_iter().collect();
+        for cmd in commands {
+            match cmd {
+                MprisCommand::PlayPause => self.toggle_pause(),
+                MprisCommand::Play if self.sink.is_paused() => self.toggle_pause(),
+                MprisCommand::Pause if !self.sink.is_paused() => self.toggle_pause(),
+                MprisCommand::Play | MprisCommand::Pause => {}
+                MprisCommand::Next => self.next_track(),
+                MprisCommand::Previous => self.prev_track(),
+                MprisCommand::Stop => {
+                    self.finish_fade();
+                    self.sink.pause();
+                    if self.sink_seek(Duration::ZERO).is_ok() {
+                        self.position_tracker.reset(Duration::ZERO);
                     }
                 }
+                MprisCommand::Seek(offset) => self.seek_by(offset as f64 / 1_000_000.0),
+                MprisCommand::SetPosition(pos) => self.seek_to(pos as f64 / 1_000_000.0),
+                MprisCommand::SetVolume(volume) => self.adjust_volume(volume as f32 - self.volume),
             }
         }
+
+        let Some(m) = &self.mpris else { return };
+        let Ok(mut shared) = m.shared.lock() else { return };
+        if self.mpris_path != self.current_path {
+            self.mpris_path = self.current_path.clone();
+            shared.track_id += 1;
+        }
+        shared.stopped = !self.track_was_loaded || self.sink.empty();
+        shared.playing = !self.sink.is_paused();
+        shared.title = self.title.clone();
+        shared.artist = self.artist.clone();
+        shared.album = self.album.clone();
+        shared.length = self.duration;
+        shared.position = self.position_tracker.estimate();
+        shared.volume = self.volume as f64;
     }
-    // Urutkan file berdasarkan nama
-    files.sort();
 
-    // 3. Init State (Kosong dulu)
-    let mut app = AppState {
-        sink,
-        _stream,
-        title: "No Track Playing".to_string(),
-        artist: "".to_string(),
-        album: "".to_string(),
-        duration: Duration::from_secs(0),
-        cover_art: None,
-        lyrics: vec![],
-        lyrics_state: ListState::default(),
+    // Perintah dari Unix socket (punini-ctl)
+    fn tick_remote(&mut self) {
+        use remote::RemoteCommand;
 
-        files,
-        file_list_state: ListState::default(),
-    };
+        let Some(r) = &self.remote else { return };
+        let requests: Vec<remote::Request> = r.requests.try_iter().collect();
+        for request in requests {
+            // Validasi dulu, baru jalankan seperti tombol
+            let result = match request.cmd {
+                RemoteCommand::Seek { .. } if !self.track_was_loaded => Err("no track playing".to_string()),
+                RemoteCommand::Volume { value } if !(0.0..=1.5).contains(&value) => {
+                    Err(format!("volume must be between 0.0 and 1.5, got {}", value))
+                }
+                _ => Ok(()),
+            };
+            if result.is_ok() {
+                match request.cmd {
+                    RemoteCommand::Pause => self.toggle_pause(),
+                    RemoteCommand::Next => self.next_track(),
+                    RemoteCommand::Prev => self.prev_track(),
+                    RemoteCommand::Shuffle => self.toggle_shuffle(),
+                    RemoteCommand::Repeat => self.repeat = self.repeat.next(),
+                    RemoteCommand::Seek { secs } => self.seek_by(secs),
+                    RemoteCommand::Volume { value } => self.adjust_volume(value - self.volume),
+                    RemoteCommand::Quit => self.shutdown.store(true, Ordering::Relaxed),
+                }
+            }
+            let _ = request.reply.send(result);
+        }
+    }
 
-    // Pilih file pertama secara default (tapi belum di-load/play)
-    if !app.files.is_empty() {
-        app.file_list_state.select(Some(0));
+    // Klik kiri di progress bar (termasuk bagian yang belum terisi) = seek ke titik itu.
+    // Scroll di panel kanan = volume naik/turun.
+    fn on_mouse(&mut self, mouse: MouseEvent) {
+        let MouseEvent { kind, column, row, .. } = mouse;
+        let pos = Position::new(column, row);
+        match kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let area = self.progress_area;
+                if area.width > 0 && area.contains(pos) && !self.duration.is_zero() {
+                    let fraction = (column - area.x) as f64 / area.width as f64;
+                    self.seek_to(self.duration.as_secs_f64() * fraction);
+                }
+            }
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown if self.player_area.contains(pos) => {
+                self.adjust_volume(if kind == MouseEventKind::ScrollUp { 0.05 } else { -0.05 });
+                self.volume_overlay = Some(Instant::now());
+            }
+            _ => {}
+        }
     }
 
-    // 4. UI Loop
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    // Kerja per tick yang tidak bergantung pada TUI (dipakai juga mode headless)
+    fn tick(&mut self) {
+        // --- Metadata dari worker ---
+        while let Ok(reply) = self.worker_rx.try_recv() {
+            match reply {
+                WorkerReply::MetaReady(meta) => self.apply_metadata(*meta),
+                WorkerReply::RatingWritten { path, stars, result } => self.rating_written(&path, stars, result),
+            }
+        }
 
-    let res = run_app(&mut terminal, &mut app);
+        // --- Sleep Timer ---
+        if self.sleep_timer.is_some_and(|deadline| Instant::now() >= deadline) {
+            self.sleep_timer = None;
+            self.sink.pause();
+            self.status = Some("Sleep timer: paused".to_string());
+        }
 
-    disable_raw_mode()?;
-    execute!(io::stdout(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+        // --- Posisi Playback ---
+        let running = self.track_was_loaded && !self.sink.empty() && !self.sink.is_paused();
+        self.position_tracker.sync(self.sink_pos(), running);
+        self.tick_cue();
+        self.tick_play_count();
 
-    if let Err(err) = res {
-        eprintln!("Error: {:?}", err);
-    }
+        // --- Gapless & Fade ---
+        self.tick_preload();
+        self.tick_fade();
 
-    Ok(())
-}
+        // --- Auto-advance ---
+        // Sink kosong setelah lagu benar-benar diputar = lagu selesai
+        if self.track_was_loaded && self.sink.empty() {
+            self.on_track_finished();
+        }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut AppState) -> Result<()> {
-    loop {
-        terminal.draw(|f| ui(f, app))?;
+        // --- MPRIS (D-Bus) ---
+        #[cfg(feature = "mpris")]
+        self.tick_mpris();
 
-        // --- Logic Sinkronisasi Lirik ---
-        let current_pos = app.sink.get_pos();
-        if !app.lyrics.is_empty() {
-            let active_idx = app.lyrics.iter().rposition(|line| line.time <= current_pos);
-            app.lyrics_state.select(active_idx);
+        // --- Remote Control (Unix socket) ---
+        self.tick_remote();
+
+        // --- Statistik (`S`) yang masih menunggu scan metadata ---
+        if self.mode == AppMode::Stats && self.stats_cache.as_ref().is_some_and(|s| !s.complete()) {
+            self.refresh_stats();
         }
+    }
 
-        // --- Event Handling ---
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') => return Ok(()),
+    // Hitung lagu sekarang sekali per pemutaran, setelah lewat PLAY_COUNT_AFTER
+    // (lagu yang cuma terpilih sebentar tidak dihitung)
+    fn tick_play_count(&mut self) {
+        if self.play_counted || self.position_tracker.estimate() < PLAY_COUNT_AFTER {
+            return;
+        }
+        let Some(path) = self.current_path.clone() else { return };
+        self.play_counted = true;
+        *self.play_counts.entry(path).or_insert(0) += 1;
+        if let Err(e) = store::save(PLAYCOUNTS_FILE, &self.play_counts) {
+            warn!("{}", e);
+        }
+        if self.sort_mode == SortMode::Plays {
+            self.sort_files();
+        }
+    }
 
-                        // Play / Pause
-                        KeyCode::Char(' ') => {
-                            if app.sink.is_paused() { app.sink.play(); }
-                            else { app.sink.pause(); }
-                        }
+    // Simpan lagu, posisi, volume & queue untuk dilanjutkan di start berikutnya
+    fn save_session(&self) {
+        let position = if self.track_was_loaded { self.sink_pos() } else { Duration::ZERO };
+        session::save(&session::Session::new(self.current_path.clone(), position, self.volume));
+        session::save_queue(&self.queue);
+    }
 
-                        // Navigasi File (Atas/Bawah/j/k)
-                        KeyCode::Up | KeyCode::Char('k') => {
-                            let i = match app.file_list_state.selected() {
-                                Some(i) => if i == 0 { app.files.len() - 1 } else { i - 1 },
-                                None => 0,
-                            };
-                            app.file_list_state.select(Some(i));
-                        }
-                        KeyCode::Down | KeyCode::Char('j') => {
-                            let i = match app.file_list_state.selected() {
-                                Some(i) => if i >= app.files.len() - 1 { 0 } else { i + 1 },
-                                None => 0,
-                            };
-                            app.file_list_state.select(Some(i));
-                        }
+    // Sesi terakhir yang lagunya masih ada di library. Lagu yang sudah tidak ada = sesi dibuang diam-diam
+    fn saved_session(&self) -> Option<session::Session> {
+        let saved = session::load()?;
+        let path = saved.current_path.as_ref()?;
+        if !self.files.contains(path) {
+            info!("session track {} no longer in library, discarding session", path.display());
+            session::remove();
+            return None;
+        }
+        Some(saved)
+    }
 
-                        // Play Selected File (Enter)
-                        KeyCode::Enter => {
-                            if let Some(i) = app.file_list_state.selected() {
-                                if let Some(path) = app.files.get(i) {
-                                    // Cloning path karena load_track butuh &Path dan app dipinjam mut
-                                    let path_clone = path.clone();
-                                    app.load_track(&path_clone);
-                                }
-                            }
-                        }
+    // Queue dari queue.m3u; file yang sudah hilang dibuang dengan peringatan di status bar
+    fn load_saved_queue(&mut self) -> VecDeque<PathBuf> {
+        let (queue, missing) = session::load_queue();
+        if !missing.is_empty() {
+            warn!("dropped {} missing files from the saved queue (first: {})", missing.len(), missing[0]);
+            self.status = Some(format!("Removed {} missing tracks from the saved queue", missing.len()));
+        }
+        queue
+    }
 
-                        _ => {}
-                    }
-                }
+    fn resume_session(&mut self, saved: &session::Session, position: Duration) {
+        let Some(path) = saved.current_path.clone() else { return };
+        self.volume = saved.volume.clamp(0.0, 1.5);
+        self.apply_volume();
+        self.select_path(&path);
+        self.load_track(&path);
+        if self.track_was_loaded && !position.is_zero() {
+            match self.sink_seek(position) {
+                Ok(()) => self.position_tracker.reset(position),
+                Err(e) => warn!("cannot restore position in {}: {}", path.display(), e),
             }
         }
     }
-}
 
-fn ui(f: &mut Frame, app: &mut AppState) {
-    // 1. Layout Utama: Kiri (Files 30%) - Kanan (Player 70%)
-    let main_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
-        .split(f.area());
+    // Judul window terminal: "▶ Artist – Title | punini", hanya dikirim kalau berubah
+    fn update_window_title(&mut self) {
+        if !self.config.update_title {
+            return;
+        }
+        let title = if self.current_path.is_some() && self.track_was_loaded {
+            let icon = if self.sink.is_paused() { '⏸' } else { '▶' };
+            format!("{} {} – {} | punini", icon, self.artist, self.title)
+        } else {
+            "punini".to_string()
+        };
+        if self.window_title.as_ref() != Some(&title) {
+            let _ = execute!(io::stdout(), SetTitle(&title));
+            self.window_title = Some(title);
+        }
+    }
 
-    // --- PANEL KIRI: FILE LIST ---
-    let files_block = Block::default().borders(Borders::ALL).title(" Playlist (Music Folder) ");
+    // Reset state untuk lagu baru & minta metadata ke worker (sink tidak disentuh)
+    fn begin_track(&mut self, path: &Path) {
+        // Catat lagu sebelumnya (urutan yang benar-benar diputar, termasuk saat shuffle)
+        if let Some(prev) = self.current_path.take()
+            && prev != path
+        {
+            self.history.push(prev);
+            if self.history.len() > HISTORY_LIMIT {
+                self.history.remove(0);
+            }
+        }
 
-    let items: Vec<ListItem> = app.files.iter().map(|path| {
-        let name = path.file_name().unwrap_or_default().to_string_lossy();
-        // Cek apakah ini file yang sedang diputar? (Optional visual hint)
-        // Disini kita render biasa saja
-        ListItem::new(name).style(Style::default())
-    }).collect();
+        // Reset Metadata Visual
+        self.title = "Loading…".to_string();
+        self.title_scroll_offset = 0;
+        self.artist = "-".to_string();
+        self.album = "-".to_string();
+        self.cover_art = None;
+        self.cover_data = None;
+        self.lyrics = vec![];
+        self.chapters = vec![];
+        self.duration = Duration::from_secs(0);
+        self.year = None;
+        self.genre = None;
+        self.composer = None;
+        self.comment = None;
+        self.tag_fields = vec![];
+        self.bitrate = None;
+        self.sample_rate = None;
+        self.channels = None;
+        self.file_size = 0;
+        self.playback_unsupported = false;
+        self.replaygain = ReplayGain::default();
+        self.current_path = Some(path.to_path_buf());
+        self.meta_ready = false;
+        self.play_counted = false;
+        self.position_tracker.reset(Duration::ZERO);
+        self.current_cue = None;
+        if !self.cue_tracks.contains_key(path)
+            && let Some(tracks) = cue::load_for(path)
+        {
+            self.cue_tracks.insert(path.to_path_buf(), tracks);
+        }
 
-    let list = List::new(items)
-        .block(files_block)
-        .highlight_style(Style::default().bg(Color::Blue).fg(Color::White).add_modifier(Modifier::BOLD))
-        .highlight_symbol("> ");
+        // Preload lama tidak berlaku lagi (thread-nya dibiarkan selesai sendiri)
+        self.preload_task = None;
+        self.preload_checked = false;
+        self.preload_target = None;
+        self.preload_queued = false;
 
-    f.render_stateful_widget(list, main_chunks[0], &mut app.file_list_state);
+        // Metadata dibaca worker; hasilnya masuk lewat apply_metadata
+        if self.worker_tx.send(WorkerMsg::LoadTrack(path.to_path_buf())).is_err() {
+            error!("metadata worker is gone");
+        }
+    }
 
-    // --- PANEL KANAN: PLAYER ---
-    // Bagi panel kanan: Vertikal (Body & Progress)
-    let right_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0), Constraint::Length(3)])
-        .split(main_chunks[1]);
+    // Gapless: decode lagu berikutnya menjelang akhir lagu, lalu antrikan di sink
+    fn tick_preload(&mut self) {
+        // 1. Source lama habis dan lagu yang di-preload mulai diputar
+        if self.preload_queued && self.sink.len() <= 1 {
+            let Some(path) = self.preload_target.clone() else { return };
+            let duration = self.preload_duration;
+            // Lagu dari queue: keluarkan dari antrian sekarang karena sudah diputar
+            if self.queue.front() == Some(&path) {
+                self.queue.pop_front();
+            }
+            self.select_path(&path);
+            self.begin_track(&path);
+            self.duration = duration;
+            return;
+        }
 
-    // Bagi Body: Kiri (Cover) - Kanan (Meta & Lyrics)
-    let player_body = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
-        .split(right_chunks[0]);
+        // 2. Decode selesai: antrikan tepat setelah source sekarang
+        if self.preload_task.as_ref().is_some_and(|task| task.is_finished()) {
+            if let Some(task) = self.preload_task.take()
+                && let Ok(Some(buffer)) = task.join()
+            {
+                self.preload_duration = buffer.total_duration().unwrap_or_default();
+                self.sink.append(buffer.speed(self.speed));
+                self.preload_queued = true;
+            }
+            return;
+        }
 
-    // Bagian Kanan (Meta & Lyrics)
-    let meta_lyrics = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(6), Constraint::Min(0)])
-        .split(player_body[1]);
+        // 3. Cukup dekat dengan akhir lagu: putuskan sekali apakah perlu preload
+        if self.preload_checked || !self.track_was_loaded || self.duration.is_zero() {
+            return;
+        }
+        if self.duration.saturating_sub(self.sink_pos()) > PRELOAD_BEFORE_END {
+            return;
+        }
+        self.preload_checked = true;
 
-    // 1. Cover Art
-    let block_cover = Block::default().borders(Borders::ALL).title(" Art ").fg(Color::Cyan);
-    let cover_area = block_cover.inner(player_body[0]);
-    f.render_widget(block_cover, player_body[0]);
+        // Repeat One memutar ulang lewat load_track; Repeat Off di lagu terakhir berhenti
+        let stops = self.repeat == RepeatMode::Off && self.is_last_in_order() && self.queue.is_empty();
+        if self.repeat == RepeatMode::One || stops {
+            return;
+        }
+        let Some(format) = self.current_format else { return };
+        let next = match self.queue.front() {
+            Some(path) => Some(path.clone()),
+            // Entry CUE mulai di INDEX 01, bukan 0:00: biarkan play_index yang load & seek
+            None => self
+                .next_effective_index()
+                .filter(|idx| !self.file_cue.contains_key(idx))
+                .and_then(|idx| self.files.get(idx).cloned()),
+        };
+        let Some(path) = next.filter(|p| is_audio_file(p)) else { return };
 
-    if let Some(protocol) = &mut app.cover_art {
-        let image = StatefulImage::new(None).resize(Resize::Fit(None));
-        f.render_stateful_widget(image, cover_area, protocol);
+        self.preload_target = Some(path.clone());
+        debug!("preloading {}", path.display());
+        self.preload_task = thread::Builder::new()
+            .name("punini-preload".to_string())
+            .spawn(move || preload_track(&path, format))
+            .map_err(|e| warn!("cannot spawn preload thread: {}", e))
+            .ok();
     }
 
-    // 2. Metadata
-    let info_text = vec![
-        Line::from(vec![Span::raw("Title : "), Span::styled(&app.title, Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow))]),
-        Line::from(vec![Span::raw("Artist: "), Span::styled(&app.artist, Style::default().add_modifier(Modifier::BOLD))]),
-        Line::from(vec![Span::raw("Album : "), Span::styled(&app.album, Style::default().fg(Color::Gray))]),
-    ];
-    let block_info = Block::default().borders(Borders::ALL).title(" Info ").padding(Padding::new(1,1,1,1));
-    f.render_widget(Paragraph::new(info_text).block(block_info), meta_lyrics[0]);
+    // Terapkan hasil worker ke state (dipanggil dari loop UI)
+    fn apply_metadata(&mut self, meta: TrackMetadata) {
+        // Balasan untuk lagu yang sudah diganti: abaikan
+        if self.current_path.as_ref() != Some(&meta.path) {
+            return;
+        }
+        if self.duration.is_zero()
+            && let Some(duration) = meta.duration
+        {
+            self.duration = duration;
+        }
+        self.chapters = meta.chapters;
+        // Field kosong disimpan sebagai None supaya barisnya tidak ditampilkan
+        // Tag dari macOS kadang NFD (huruf + aksen terpisah); semua teks disimpan sebagai NFC
+        let non_empty = |s: Option<String>| s.filter(|s| !s.trim().is_empty()).map(|s| nfc(&s));
+        self.year = meta.year;
+        self.genre = non_empty(meta.genre);
+        self.composer = non_empty(meta.composer);
+        self.comment = non_empty(meta.comment);
+        self.tag_fields = meta.tag_fields.into_iter().map(|(key, value)| (key, nfc(&value))).collect();
+        self.bitrate = meta.bitrate;
+        self.sample_rate = meta.sample_rate;
+        self.channels = meta.channels;
+        self.file_size = meta.file_size.unwrap_or(0);
+        self.replaygain = meta.replaygain;
+        self.apply_volume();
 
-    // 3. Lyrics
-    let block_lyrics = Block::default().borders(Borders::ALL).title(" Lyrics ");
-    if app.lyrics.is_empty() {
-        f.render_widget(Paragraph::new("No lyrics.").block(block_lyrics).alignment(Alignment::Center), meta_lyrics[1]);
-    } else {
-        let items: Vec<ListItem> = app.lyrics.iter().map(|line| {
-            let time_str = format!("[{:02}:{:02}] ", line.time.as_secs()/60, line.time.as_secs()%60);
-            ListItem::new(Line::from(vec![
-                Span::styled(time_str, Style::default().fg(Color::DarkGray)),
-                Span::raw(&line.text),
-            ]))
-        }).collect();
+        #[cfg(feature = "mpris")]
+        if let Some(m) = &self.mpris
+            && let Ok(mut shared) = m.shared.lock()
+        {
+            shared.art_url = meta.cover.as_deref().and_then(mpris::write_art);
+        }
 
-        let lyrics_list = List::new(items)
-            .block(block_lyrics)
-            .highlight_style(Style::default().bg(Color::Cyan).fg(Color::Black).add_modifier(Modifier::BOLD))
-            .highlight_symbol(">> ");
+        // 1. Cover: thumbnail sudah di-decode worker, di sini tinggal dibuat protocol-nya
+        let cover_image = meta.cover_image;
+        #[cfg(feature = "notify")]
+        let notify_icon = cover_image.as_ref().filter(|_| self.config.notify).and_then(notify::write_thumbnail);
+        self.cover_data = meta.cover;
+        if let Some(img) = cover_image.filter(|_| !self.headless) {
+            match Picker::from_termios() {
+                Ok(mut picker) => self.cover_art = Some(picker.new_resize_protocol(img)),
+                Err(e) => debug!("no terminal image support: {}", e),
+            }
+        }
+
+        // 2. Lyrics
+        let mut lrc_meta = LrcMetadata::default();
+        if let Some(source) = &meta.lyrics {
+            let (lines, lrc) = lyrics::parse(source, self.duration);
+            self.lyrics = lines;
+            lrc_meta = lrc;
+        }
+
+        // 3. Tag diutamakan; header LRC ([ti:], [ar:], [al:]) dipakai kalau tag kosong
+        if meta.tags_read {
+            self.title = meta.title.or(lrc_meta.title).map_or("Unknown Title".to_string(), |s| nfc(&s));
+            self.artist = meta.artist.or(lrc_meta.artist).map_or("Unknown Artist".to_string(), |s| nfc(&s));
+            self.album = meta.album.or(lrc_meta.album).map_or("Unknown Album".to_string(), |s| nfc(&s));
+        } else {
+            // Jika gagal baca tag, pakai header LRC atau nama file
+            self.title = lrc_meta.title
+                .unwrap_or_else(|| display_file_name(&meta.path));
+            if let Some(artist) = lrc_meta.artist {
+                self.artist = artist;
+            }
+            if let Some(album) = lrc_meta.album {
+                self.album = album;
+            }
+        }
+        self.meta_ready = true;
+        // Judul dari tag file diganti lagi dengan judul lagu CUE di tick berikutnya
+        self.current_cue = None;
+
+        #[cfg(feature = "notify")]
+        if self.config.notify {
+            let previous = self.notification_handle.take();
+            self.notification_handle = notify::show(&self.title, &self.artist, notify_icon.as_deref(), previous);
+        }
+    }
+
+    // Naik/turunkan volume, dibatasi 0% - 150%
+    fn adjust_volume(&mut self, delta: f32) {
+        // Dibulatkan ke 0.01 supaya step 0.05 tidak menumpuk error float
+        self.volume = ((self.volume + delta).clamp(0.0, 1.5) * 100.0).round() / 100.0;
+        self.apply_volume();
+    }
+
+    // Gain ReplayGain yang berlaku (dB, peak). Mode album tanpa tag album memakai gain track.
+    fn rg_gain(&self) -> Option<(f32, Option<f32>)> {
+        if !self.rg_enabled {
+            return None;
+        }
+        let rg = &self.replaygain;
+        let track = rg.track_gain.map(|g| (g, rg.track_peak));
+        match self.rg_mode {
+            RgMode::Track => track,
+            RgMode::Album => rg.album_gain.map(|g| (g, rg.album_peak)).or(track),
+        }
+    }
+
+    // Volume ke sink: volume user x ReplayGain. Gain tidak boleh mendorong peak lewat 1.0,
+    // dan hasil akhirnya dibatasi 0.0 - 2.0 supaya tidak clipping.
+    fn output_volume(&self) -> f32 {
+        let multiplier = match self.rg_gain() {
+            Some((gain_db, peak)) => {
+                let linear = 10.0_f32.powf(gain_db / 20.0);
+                match peak.filter(|&p| p > 0.0) {
+                    Some(peak) => linear.min(1.0 / peak),
+                    None => linear,
+                }
+            }
+            None => 1.0,
+        };
+        (self.volume * multiplier).clamp(0.0, 2.0)
+    }
+
+    fn apply_volume(&mut self) {
+        self.sink.set_volume(self.output_volume());
+    }
+
+    // `R`: ReplayGain on/off
+    fn toggle_replaygain(&mut self) {
+        self.rg_enabled = !self.rg_enabled;
+        self.apply_volume();
+        self.status = Some(format!("ReplayGain: {}", if self.rg_enabled { self.rg_mode.label() } else { "off" }));
+    }
+
+    // Ctrl+R: ganti sumber gain (track / album)
+    fn toggle_rg_mode(&mut self) {
+        self.rg_mode = self.rg_mode.toggle();
+        self.apply_volume();
+        self.status = Some(format!("ReplayGain mode: {}", self.rg_mode.label()));
+    }
+
+    // Geser posisi playback relatif ke posisi sekarang (detik, boleh negatif)
+    fn seek_by(&mut self, delta_secs: f64) {
+        self.seek_to(self.position_tracker.estimate().as_secs_f64() + delta_secs);
+    }
+
+    // Lompat ke posisi absolut (detik), dibatasi 0 - durasi
+    fn seek_to(&mut self, target_secs: f64) {
+        if !self.track_was_loaded {
+            return;
+        }
+        let mut target = target_secs.max(0.0);
+        if !self.duration.is_zero() {
+            target = target.min(self.duration.as_secs_f64());
+        }
+        let target = Duration::from_secs_f64(target);
+        match self.sink_seek(target) {
+            Ok(()) => {
+                self.position_tracker.reset(target);
+                self.last_error = None;
+            }
+            Err(e) => {
+                warn!("seek failed: {}", e);
+                self.last_error = Some(format!("Seek failed: {}", e));
+            }
+        }
+    }
+
+    // `}` / `{`: lompat ke awal chapter berikutnya / sebelumnya. Lebih dari 2 detik
+    // setelah awal chapter, `{` kembali ke awal chapter itu dulu (seperti prev track)
+    fn seek_chapter(&mut self, forward: bool) {
+        let pos = self.position_tracker.estimate();
+        let target = if forward {
+            self.chapters.iter().find(|c| c.start > pos)
+        } else {
+            self.chapters.iter().rev().find(|c| c.start + Duration::from_secs(2) < pos).or(self.chapters.first())
+        };
+        match target.map(|c| c.start) {
+            Some(start) => self.seek_to(start.as_secs_f64()),
+            None if self.chapters.is_empty() => self.status = Some("No chapters in this track".to_string()),
+            None => self.status = Some("Last chapter".to_string()),
+        }
+    }
+
+    // Index (di `files`) dari lagu yang sedang diputar, atau posisi kursor
+    fn current_index(&self) -> Option<usize> {
+        let path = self.current_path.as_ref();
+        // File CUE muncul sekali per lagu virtual: cari entry lagu yang sedang diputar
+        let cue_entry = self.current_cue.and_then(|cue| {
+            (0..self.files.len()).find(|&i| Some(&self.files[i]) == path && self.file_cue.get(&i) == Some(&cue))
+        });
+        cue_entry
+            .or_else(|| path.and_then(|p| self.files.iter().position(|f| f == p)))
+            .or(self.file_list_state.selected())
+    }
+
+    // Index lagu berikutnya/sebelumnya: urut biasa, atau ikut `shuffle_order`
+    fn effective_index(&self, step: isize) -> Option<usize> {
+        let len = self.files.len();
+        if len == 0 {
+            return None;
+        }
+        let Some(current) = self.current_index() else { return Some(0) };
+        if self.shuffle && self.shuffle_order.len() == len {
+            let pos = self.shuffle_order.iter().position(|&i| i == current).unwrap_or(0);
+            let next_pos = (pos as isize + step).rem_euclid(len as isize) as usize;
+            Some(self.shuffle_order[next_pos])
+        } else {
+            Some((current as isize + step).rem_euclid(len as isize) as usize)
+        }
+    }
+
+    fn next_effective_index(&self) -> Option<usize> {
+        self.effective_index(1)
+    }
+
+    fn prev_effective_index(&self) -> Option<usize> {
+        self.effective_index(-1)
+    }
+
+    // Pindahkan kursor browser ke `path` kalau ada di `files`
+    fn select_path(&mut self, path: &Path) {
+        if let Some(idx) = self.files.iter().position(|f| f == path) {
+            self.file_list_state.select(Some(idx));
+        }
+    }
+
+    fn play_index(&mut self, idx: usize) {
+        let Some(path) = self.files.get(idx).cloned() else { return };
+        self.file_list_state.select(Some(idx));
+
+        // Lagu CUE: file yang sama tidak di-load ulang, cukup seek ke INDEX 01
+        let cue = self.file_cue.get(&idx).and_then(|&n| self.cue_tracks.get(&path)?.get(n).map(|t| (n, t.start)));
+        if cue.is_none() || !self.track_was_loaded || self.sink.empty() || self.current_path.as_ref() != Some(&path) {
+            self.load_track(&path);
+        }
+        if let Some((n, start)) = cue {
+            self.seek_to(start.as_secs_f64());
+            self.current_cue = Some(n);
+            self.apply_cue_track();
+        }
+    }
+
+    // Lagu CUE yang sedang diputar
+    fn cue_track(&self) -> Option<&CueTrack> {
+        let tracks = self.cue_tracks.get(self.current_path.as_ref()?)?;
+        tracks.get(self.current_cue?)
+    }
+
+    // Judul, artist & highlight file list mengikuti lagu CUE sekarang
+    fn apply_cue_track(&mut self) {
+        let Some(track) = self.cue_track().cloned() else { return };
+        self.title = track.title;
+        if let Some(artist) = track.artist {
+            self.artist = artist;
+        }
+        if let Some(idx) = self.current_index()
+            && self.mode == AppMode::Normal
+        {
+            self.file_list_state.select(Some(idx));
+        }
+    }
+
+    // Tiap tick: posisi sudah masuk lagu CUE berikutnya?
+    fn tick_cue(&mut self) {
+        if !self.meta_ready {
+            return;
+        }
+        let Some(tracks) = self.current_path.as_ref().and_then(|p| self.cue_tracks.get(p)) else { return };
+        let pos = self.position_tracker.estimate();
+        // Sebelum INDEX 01 lagu pertama (pregap) dianggap lagu pertama
+        let idx = tracks.iter().rposition(|t| t.start <= pos).unwrap_or(0);
+        if Some(idx) == self.current_cue {
+            return;
+        }
+        // Repeat One di file CUE: ulang lagu virtual, bukan seluruh file
+        if self.repeat == RepeatMode::One
+            && let Some(current) = self.current_cue
+            && idx > current
+        {
+            let start = tracks[current].start;
+            self.seek_to(start.as_secs_f64());
+            return;
+        }
+        self.current_cue = Some(idx);
+        self.apply_cue_track();
+    }
+
+    // Rentang (awal, panjang) lagu CUE sekarang di dalam file, untuk progress bar
+    fn cue_span(&self) -> Option<(Duration, Duration)> {
+        let track = self.cue_track()?;
+        let end = track.end.unwrap_or(self.duration);
+        Some((track.start, end.saturating_sub(track.start)))
+    }
+
+    // File yang punya .cue diganti dengan satu entry per lagu virtual
+    fn expand_cue_files(&mut self) {
+        let entries: Vec<(PathBuf, Option<String>)> = self.files.drain(..)
+            .enumerate()
+            .map(|(i, path)| (path, self.display_names.remove(&i)))
+            .collect();
+        self.display_names.clear();
+        self.file_cue.clear();
+        for (path, name) in entries {
+            let tracks = match self.cue_tracks.get(&path) {
+                Some(tracks) => Some(tracks.clone()),
+                None if is_audio_file(&path) => cue::load_for(&path),
+                None => None,
+            };
+            let Some(tracks) = tracks else {
+                if let Some(name) = name {
+                    self.display_names.insert(self.files.len(), name);
+                }
+                self.files.push(path);
+                continue;
+            };
+            for (n, track) in tracks.iter().enumerate() {
+                let label = match &track.artist {
+                    Some(artist) => format!("{:02}. {} – {}", n + 1, track.title, artist),
+                    None => format!("{:02}. {}", n + 1, track.title),
+                };
+                self.display_names.insert(self.files.len(), label);
+                self.file_cue.insert(self.files.len(), n);
+                self.files.push(path.clone());
+            }
+            self.cue_tracks.insert(path, tracks);
+        }
+    }
+
+    // Apakah lagu sekarang adalah yang terakhir dalam urutan (biasa/shuffle)
+    fn is_last_in_order(&self) -> bool {
+        let Some(current) = self.current_index() else { return false };
+        let last = self.files.len().saturating_sub(1);
+        if self.shuffle && self.shuffle_order.len() == self.files.len() {
+            self.shuffle_order.last() == Some(&current)
+        } else {
+            current == last
+        }
+    }
+
+    // Dipanggil saat lagu selesai sendiri; perilaku mengikuti mode repeat
+    fn on_track_finished(&mut self) {
+        // Lagu berikutnya diambil dari tab asal lagu, bukan tab yang sedang dilihat
+        if let Some(tab) = self.playing_tab.filter(|&t| t != self.active_tab) {
+            let active = self.active_tab;
+            self.switch_tab(tab);
+            self.on_track_finished();
+            self.switch_tab(active);
+            return;
+        }
+        match self.repeat {
+            RepeatMode::One => {
+                if let Some(path) = self.current_path.clone() {
+                    // Lagu CUE terakhir di file: ulang dari INDEX 01-nya, bukan dari awal file
+                    let cue_start = self.cue_track().map(|t| t.start);
+                    self.load_track(&path);
+                    if let Some(start) = cue_start {
+                        self.seek_to(start.as_secs_f64());
+                    }
+                }
+            }
+            // Queue didahulukan daripada urutan library
+            _ if !self.queue.is_empty() => {
+                if let Some(path) = self.queue.pop_front() {
+                    self.select_path(&path);
+                    self.load_track(&path);
+                }
+            }
+            RepeatMode::All => self.next_track(),
+            RepeatMode::Off => {
+                if self.is_last_in_order() {
+                    // Akhir playlist: berhenti, jangan cek lagi sampai ada lagu baru
+                    self.track_was_loaded = false;
+                } else {
+                    self.next_track();
+                }
+            }
+        }
+    }
+
+    // Lanjut ke lagu setelah yang sedang diputar (wrap ke awal playlist)
+    fn next_track(&mut self) {
+        self.on_playing_tab(|app| {
+            if let Some(idx) = app.next_effective_index() {
+                app.play_index(idx);
+            }
+        });
+    }
+
+    // Kembali ke lagu yang diputar sebelumnya (tombol p / Backspace)
+    fn history_back(&mut self) {
+        let Some(path) = self.history.pop() else {
+            self.status = Some("No history".to_string());
+            return;
+        };
+        // Lagu sekarang tidak dicatat lagi, supaya p berulang kali terus mundur
+        self.current_path = None;
+        self.select_path(&path);
+        self.load_track(&path);
+    }
+
+    fn prev_track(&mut self) {
+        self.on_playing_tab(|app| {
+            if let Some(idx) = app.prev_effective_index() {
+                app.play_index(idx);
+            }
+        });
+    }
+
+    // n / N saat melihat tab lain: tetap maju / mundur di tab asal lagu
+    fn on_playing_tab(&mut self, action: impl FnOnce(&mut Self)) {
+        let active = self.active_tab;
+        match self.playing_tab.filter(|&t| t != active) {
+            Some(tab) => {
+                self.switch_tab(tab);
+                action(self);
+                self.switch_tab(active);
+            }
+            None => action(self),
+        }
+    }
+
+    // --- Tab ---
+
+    // Simpan isi file list ke tab aktif, lalu pakai isi tab `idx`
+    fn switch_tab(&mut self, idx: usize) {
+        if idx == self.active_tab || idx >= self.tabs.len() {
+            return;
+        }
+        let stashed = &mut self.tabs[self.active_tab];
+        stashed.files = std::mem::take(&mut self.files);
+        stashed.file_list_state = std::mem::take(&mut self.file_list_state);
+        stashed.display_names = std::mem::take(&mut self.display_names);
+        stashed.file_cue = std::mem::take(&mut self.file_cue);
+        stashed.shuffle_order = std::mem::take(&mut self.shuffle_order);
+        stashed.virtual_playlist = self.virtual_playlist.take();
+        stashed.files_dir = self.files_dir.take();
+
+        let tab = &mut self.tabs[idx];
+        self.files = std::mem::take(&mut tab.files);
+        self.file_list_state = std::mem::take(&mut tab.file_list_state);
+        self.display_names = std::mem::take(&mut tab.display_names);
+        self.file_cue = std::mem::take(&mut tab.file_cue);
+        self.shuffle_order = std::mem::take(&mut tab.shuffle_order);
+        self.virtual_playlist = tab.virtual_playlist.take();
+        self.files_dir = tab.files_dir.take();
+        self.active_tab = idx;
+        self.filtered_files.clear();
+    }
+
+    // Alt+1..9
+    fn select_tab(&mut self, idx: usize) {
+        if idx >= self.tabs.len() {
+            return;
+        }
+        self.switch_tab(idx);
+        self.status = Some(format!("Tab {}: {}", idx + 1, self.tabs[idx].name));
+    }
+
+    // Ctrl+T: tab baru berisi folder yang sedang dibuka (tampilan dua panel) atau folder musik
+    fn new_tab(&mut self) {
+        let dir = self.tree_dir.clone().filter(|_| self.tree_view).unwrap_or_else(|| self.config.music_dir.clone());
+        self.tabs.push(PlaylistTab::new(display_file_name(&dir)));
+        self.switch_tab(self.tabs.len() - 1);
+        let depth = if self.tree_view { 0 } else { self.config.max_scan_depth };
+        self.replace_files(&dir, depth);
+        if self.shuffle {
+            self.shuffle = false;
+            self.toggle_shuffle();
+        }
+        self.status = Some(format!("Tab {}: {}", self.active_tab + 1, self.tabs[self.active_tab].name));
+    }
+
+    // Ctrl+W: tutup tab aktif; lagu yang sedang diputar tetap jalan
+    fn close_tab(&mut self) {
+        if self.tabs.len() == 1 {
+            self.last_error = Some("Error: cannot close the last tab".to_string());
+            return;
+        }
+        let closed = self.active_tab;
+        // Pindah dulu ke tab tetangga, baru tab lama dibuang
+        self.switch_tab(if closed + 1 < self.tabs.len() { closed + 1 } else { closed - 1 });
+        let tab = self.tabs.remove(closed);
+        if self.active_tab > closed {
+            self.active_tab -= 1;
+        }
+        self.playing_tab = match self.playing_tab {
+            Some(t) if t == closed => None,
+            Some(t) if t > closed => Some(t - 1),
+            other => other,
+        };
+        self.status = Some(format!("Closed tab {}", tab.name));
+    }
+
+    // `M` di file list: pindahkan file di kursor ke tab berikutnya
+    fn move_selected_to_next_tab(&mut self) {
+        if self.tabs.len() == 1 {
+            self.last_error = Some("Error: no other tab (Ctrl+T opens one)".to_string());
+            return;
+        }
+        let Some(idx) = self.file_list_state.selected().filter(|&i| i < self.files.len()) else { return };
+
+        // Index sesudahnya bergeser satu, termasuk nama #EXTINF dan nomor lagu CUE
+        let path = self.files.remove(idx);
+        let name = self.display_names.remove(&idx);
+        let cue = self.file_cue.remove(&idx);
+        shift_indices_after(&mut self.display_names, idx);
+        shift_indices_after(&mut self.file_cue, idx);
+        self.file_list_state.select((!self.files.is_empty()).then(|| idx.min(self.files.len() - 1)));
+        if self.shuffle {
+            self.shuffle = false;
+            self.toggle_shuffle();
+        }
+
+        let target_idx = (self.active_tab + 1) % self.tabs.len();
+        let target = &mut self.tabs[target_idx];
+        // Tab tujuan bukan lagi hasil scan folder: file baru dari watcher tidak masuk ke sana
+        target.files_dir = None;
+        let at = target.files.len();
+        if let Some(name) = name {
+            target.display_names.insert(at, name);
+        }
+        if let Some(cue) = cue {
+            target.file_cue.insert(at, cue);
+        }
+        if target.file_list_state.selected().is_none() {
+            target.file_list_state.select(Some(0));
+        }
+        // Urutan shuffle tab tujuan tidak lagi cocok; dibuat ulang begitu tabnya dipakai
+        target.shuffle_order.clear();
+        target.files.push(path.clone());
+        if self.current_path.as_ref() == Some(&path) && self.playing_tab == Some(self.active_tab) {
+            self.playing_tab = Some(target_idx);
+        }
+        self.status = Some(format!("Moved {} to tab {}", display_file_name(&path), target_idx + 1));
+    }
+
+    // Shuffle on/off. Lagu sekarang ditaruh di awal urutan acak supaya semua lagu kebagian
+    fn toggle_shuffle(&mut self) {
+        self.shuffle = !self.shuffle;
+        if self.shuffle {
+            let mut order: Vec<usize> = (0..self.files.len()).collect();
+            order.shuffle(&mut rand::rng());
+            if let Some(current) = self.current_index()
+                && let Some(pos) = order.iter().position(|&i| i == current)
+            {
+                order.swap(0, pos);
+            }
+            self.shuffle_order = order;
+        } else {
+            self.shuffle_order.clear();
+        }
+    }
+
+    // Buka playlist M3U/M3U8: isi browser diganti dengan lagu-lagu di dalamnya
+    fn load_playlist(&mut self, path: &Path) {
+        let content = match fs::read(path) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Err(e) => {
+                warn!("failed to read playlist {}: {}", path.display(), e);
+                self.last_error = Some(format!("Failed to read {}: {}", path.display(), e));
+                return;
+            }
+        };
+
+        let (entries, skipped) = parse_m3u(&content, path.parent().unwrap_or(Path::new("")));
+        if entries.is_empty() {
+            self.last_error = Some(format!("Playlist {} has no playable entries", path.display()));
+            return;
+        }
+
+        self.files.clear();
+        self.display_names.clear();
+        for (i, (file, name)) in entries.into_iter().enumerate() {
+            if let Some(name) = name {
+                self.display_names.insert(i, name);
+            }
+            self.files.push(file);
+        }
+        self.expand_cue_files();
+        self.file_list_state.select(Some(0));
+        spawn_meta_scan(self.audio_files(), self.meta_cache.clone());
+        // Tidak di-sort: urutan di file .m3u adalah urutan yang diinginkan
+        self.virtual_playlist = Some(VirtualPlaylist::File(path.to_path_buf()));
+        self.files_dir = None;
+
+        for entry in &skipped {
+            warn!("playlist {}: skipping missing entry {}", path.display(), entry);
+        }
+        self.last_error = (!skipped.is_empty()).then(|| {
+            format!("Skipped {} missing playlist entries (first: {})", skipped.len(), skipped[0])
+        });
+    }
+
+    // `a` = taruh di akhir queue, `A` = taruh paling depan (diputar berikutnya)
+    fn enqueue_selected(&mut self, next: bool) {
+        let Some(path) = self.file_list_state.selected().and_then(|i| self.files.get(i)).cloned() else { return };
+        if !is_audio_file(&path) {
+            return;
+        }
+        let name = display_file_name(&path);
+        if next {
+            self.queue.push_front(path);
+            self.status = Some(format!("Playing next: {}", name));
+        } else {
+            self.queue.push_back(path);
+            self.status = Some(format!("Queued: {}", name));
+        }
+        self.queue_changed();
+    }
+
+    // Setelah queue diubah user: simpan ke queue.m3u. Preload yang sudah jalan
+    // mungkin bukan lagu berikutnya lagi (yang sudah masuk sink tidak bisa ditarik)
+    fn queue_changed(&mut self) {
+        if !self.preload_queued {
+            self.preload_task = None;
+            self.preload_target = None;
+            self.preload_checked = false;
+        }
+        session::save_queue(&self.queue);
+    }
+
+    // Entry depan yang sudah di-preload ke sink: diputar berikutnya apa pun urutan queue
+    fn queue_front_buffered(&self) -> bool {
+        self.preload_queued && self.queue.front().is_some() && self.queue.front() == self.preload_target.as_ref()
+    }
+
+    fn remove_selected_from_queue(&mut self) {
+        let Some(i) = self.queue_state.selected() else { return };
+        self.queue.remove(i);
+        if self.queue.is_empty() {
+            self.queue_state.select(None);
+        } else {
+            self.queue_state.select(Some(i.min(self.queue.len() - 1)));
+        }
+        self.queue_changed();
+    }
+
+    // Ctrl+Up/Down: tukar dengan tetangga; Ctrl+Home/End (delta besar): pindah ke ujung.
+    // Kursor ikut entry yang dipindah, jadi tombol yang sama bisa ditekan berulang
+    fn move_queue_entry(&mut self, delta: isize) {
+        let Some(i) = self.queue_state.selected().filter(|&i| i < self.queue.len()) else { return };
+        let first = usize::from(self.queue_front_buffered());
+        if i < first {
+            self.status = Some("Already buffered: plays next".to_string());
+            return;
+        }
+        let target = (i as isize).saturating_add(delta).clamp(first as isize, self.queue.len() as isize - 1) as usize;
+        if target == i {
+            return;
+        }
+        move_entry(&mut self.queue, i, target);
+        self.queue_state.select(Some(target));
+        self.queue_changed();
+    }
+
+    fn move_queue_cursor(&mut self, delta: isize) {
+        if self.queue.is_empty() {
+            return;
+        }
+        let len = self.queue.len() as isize;
+        let i = self.queue_state.selected().map_or(0, |i| (i as isize + delta).rem_euclid(len));
+        self.queue_state.select(Some(i as usize));
+    }
+
+    // Enter di panel queue: putar entry itu sekarang (dan keluarkan dari queue)
+    fn play_from_queue(&mut self) {
+        let Some(i) = self.queue_state.selected() else { return };
+        let Some(path) = self.queue.get(i).cloned() else { return };
+        self.remove_selected_from_queue();
+        self.select_path(&path);
+        self.load_track(&path);
+    }
+
+    fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            // Tampilan dua panel: Tab pindah antara pohon & file, queue lewat Q
+            Focus::Browser if self.tree_view => Focus::Tree,
+            Focus::Tree => Focus::Lyrics,
+            Focus::Browser => Focus::Queue,
+            Focus::Queue => Focus::Lyrics,
+            Focus::Lyrics => Focus::Browser,
+        };
+        if self.focus == Focus::Queue && self.queue_state.selected().is_none() && !self.queue.is_empty() {
+            self.queue_state.select(Some(0));
+        }
+    }
+
+    // `b`: bookmark file yang dipilih di browser (bukan lagu yang sedang diputar)
+    fn bookmark_selected(&mut self) {
+        let Some(path) = self.file_list_state.selected().and_then(|i| self.files.get(i)).cloned() else { return };
+        if !is_audio_file(&path) {
+            return;
+        }
+        let name = display_file_name(&path);
+        if self.bookmarks.contains(&path) {
+            self.status = Some(format!("Already bookmarked: {}", name));
+            return;
+        }
+        self.bookmarks.push(path);
+        self.status = Some(format!("Bookmarked: {}", name));
+        self.save_bookmarks();
+    }
+
+    fn save_bookmarks(&mut self) {
+        if let Err(e) = store::save(BOOKMARKS_FILE, &self.bookmarks) {
+            warn!("{}", e);
+            self.last_error = Some(e);
+        }
+    }
+
+    // `1`-`5`: rating lagu yang sedang diputar, `0` = hapus rating
+    fn rate_current(&mut self, stars: u8) {
+        let Some(path) = self.current_path.clone() else {
+            self.status = Some("No track playing".to_string());
+            return;
+        };
+        if stars == 0 {
+            self.ratings.remove(&path);
+        } else {
+            self.ratings.insert(path.clone(), stars);
+        }
+        if let Err(e) = store::save(RATINGS_FILE, &self.ratings) {
+            warn!("{}", e);
+            self.last_error = Some(e);
+            return;
+        }
+        self.status = Some(rating_message(stars));
+        // Tag ditulis worker (file di-copy utuh); hasilnya masuk lewat rating_written
+        if self.worker_tx.send(WorkerMsg::WriteRating { path, stars }).is_err() {
+            self.last_error = Some("Rating tag not written: metadata worker stopped".to_string());
+        }
+        if self.sort_mode == SortMode::Rating {
+            self.sort_files();
+        }
+    }
+
+    fn rating_written(&mut self, path: &Path, stars: u8, result: Result<bool, String>) {
+        if let Err(e) = result {
+            warn!("cannot write rating tag to {}: {}", path.display(), e);
+            self.status = Some(format!("{} (tag not written: {})", rating_message(stars), e));
+        }
+    }
+
+    // `B`: buka overlay bookmark
+    fn open_bookmarks(&mut self) {
+        if self.bookmarks.is_empty() {
+            self.status = Some("No bookmarks (press b to add)".to_string());
+            return;
+        }
+        let selected = self.bookmarks_state.selected().unwrap_or(0).min(self.bookmarks.len() - 1);
+        self.bookmarks_state.select(Some(selected));
+        self.mode = AppMode::Bookmarks;
+    }
+
+    fn move_bookmark_cursor(&mut self, delta: isize) {
+        if self.bookmarks.is_empty() {
+            return;
+        }
+        let len = self.bookmarks.len() as isize;
+        let i = self.bookmarks_state.selected().map_or(0, |i| (i as isize + delta).rem_euclid(len));
+        self.bookmarks_state.select(Some(i as usize));
+    }
+
+    // Enter di overlay: putar bookmark yang dipilih
+    fn play_bookmark(&mut self) {
+        let Some(path) = self.bookmarks_state.selected().and_then(|i| self.bookmarks.get(i)).cloned() else { return };
+        self.mode = AppMode::Normal;
+        if !path.is_file() {
+            self.last_error = Some(format!("Bookmarked file not found: {}", path.display()));
+            return;
+        }
+        self.select_path(&path);
+        self.load_track(&path);
+    }
+
+    fn remove_selected_bookmark(&mut self) {
+        let Some(i) = self.bookmarks_state.selected().filter(|&i| i < self.bookmarks.len()) else { return };
+        self.bookmarks.remove(i);
+        self.save_bookmarks();
+        if self.bookmarks.is_empty() {
+            self.bookmarks_state.select(None);
+            self.mode = AppMode::Normal;
+        } else {
+            self.bookmarks_state.select(Some(i.min(self.bookmarks.len() - 1)));
+        }
+    }
+
+    // Isi menu `P`. Genre baru bisa didaftar setelah metadata seluruh library terbaca;
+    // sebelum itu yang dikembalikan progresnya (sudah terbaca, total)
+    fn playlist_menu(&self) -> (Vec<VirtualPlaylist>, Option<(usize, usize)>) {
+        let mut items = vec![
+            VirtualPlaylist::RecentlyAdded(VIRTUAL_PLAYLIST_LEN),
+            VirtualPlaylist::MostPlayed(VIRTUAL_PLAYLIST_LEN),
+            VirtualPlaylist::Unplayed,
+        ];
+        let Ok(meta) = self.meta_cache.lock() else { return (items, None) };
+        let audio: Vec<&PathBuf> = self.library.iter().filter(|p| is_audio_file(p)).collect();
+        let loaded = audio.iter().filter(|p| meta.contains_key(**p)).count();
+        if loaded < audio.len() {
+            return (items, Some((loaded, audio.len())));
+        }
+        let genres: BTreeSet<String> = audio.iter()
+            .filter_map(|p| meta.get(*p).and_then(|m| m.genre.clone()))
+            .filter(|g| !g.trim().is_empty())
+            .collect();
+        items.extend(genres.into_iter().map(VirtualPlaylist::ByGenre));
+        (items, None)
+    }
+
+    // `P`: buka menu playlist virtual. Metadata seluruh library ikut dibaca untuk daftar genre
+    fn open_playlist_menu(&mut self) {
+        spawn_meta_scan(self.library.iter().filter(|p| is_audio_file(p)).cloned().collect(), self.meta_cache.clone());
+        self.playlists_state.select(Some(self.playlists_state.selected().unwrap_or(0)));
+        self.mode = AppMode::VirtualPlaylists;
+    }
+
+    fn move_playlist_cursor(&mut self, delta: isize) {
+        let len = self.playlist_menu().0.len() as isize;
+        let i = self.playlists_state.selected().map_or(0, |i| (i as isize + delta).rem_euclid(len));
+        self.playlists_state.select(Some(i as usize));
+    }
+
+    // Enter di menu: isi file list dengan playlist yang dipilih
+    fn open_selected_playlist(&mut self) {
+        let items = self.playlist_menu().0;
+        let Some(playlist) = self.playlists_state.selected().and_then(|i| items.get(i)).cloned() else { return };
+        self.mode = AppMode::Normal;
+
+        let audio = self.library.iter().filter(|p| is_audio_file(p)).cloned();
+        let files: Vec<PathBuf> = match &playlist {
+            VirtualPlaylist::RecentlyAdded(n) => {
+                let mut files: Vec<PathBuf> = audio.collect();
+                files.sort_by_cached_key(|p| std::cmp::Reverse(fs::metadata(p).and_then(|m| m.modified()).ok()));
+                files.truncate(*n);
+                files
+            }
+            VirtualPlaylist::MostPlayed(n) => most_played(audio, &self.play_counts, *n),
+            VirtualPlaylist::Unplayed => audio.filter(|p| self.play_counts.get(p).is_none_or(|&c| c == 0)).collect(),
+            VirtualPlaylist::ByGenre(genre) => {
+                let meta = self.meta_cache.lock().ok();
+                audio.filter(|p| meta.as_ref().and_then(|m| m.get(p)?.genre.as_ref()) == Some(genre)).collect()
+            }
+            VirtualPlaylist::File(path) => return self.load_playlist(&path.clone()),
+        };
+        if files.is_empty() {
+            self.status = Some(format!("{}: no tracks", playlist.label()));
+            return;
+        }
+        self.status = Some(format!("{}: {} tracks", playlist.label(), files.len()));
+        // Urutan playlist (terbaru / terbanyak diputar) dipertahankan, tidak di-sort ulang
+        self.set_files(files);
+        self.virtual_playlist = Some(playlist);
+        self.focus = Focus::Browser;
+    }
+
+    // Esc: tinggalkan playlist virtual (atau .m3u), kembali ke folder biasa
+    fn close_virtual_playlist(&mut self) {
+        self.virtual_playlist = None;
+        match self.tree.selected_path().filter(|_| self.tree_view) {
+            Some(dir) => self.show_dir(&dir),
+            None => {
+                let dir = self.config.music_dir.clone();
+                self.replace_files(&dir, self.config.max_scan_depth);
+            }
+        }
+    }
+
+    // `D`: menu output audio, kursor di device yang sedang dipakai
+    fn open_devices(&mut self) {
+        self.devices = device::names();
+        if self.devices.is_empty() {
+            self.last_error = Some("No audio output devices found".to_string());
+            return;
+        }
+        let current = self.devices.iter().position(|d| Some(d) == self.device_name.as_ref());
+        self.devices_state.select(Some(current.unwrap_or(0)));
+        self.mode = AppMode::Devices;
+    }
+
+    fn move_device_cursor(&mut self, delta: isize) {
+        let len = self.devices.len() as isize;
+        let i = self.devices_state.selected().map_or(0, |i| (i as isize + delta).rem_euclid(len));
+        self.devices_state.select(Some(i as usize));
+    }
+
+    // Enter di menu `D`: pindah output. Stream & sink baru dibuat dulu, jadi kalau gagal
+    // output lama tetap jalan; jeda hanya selama fade-out + seek di device baru.
+    fn switch_device(&mut self) {
+        let Some(query) = self.devices_state.selected().and_then(|i| self.devices.get(i)).cloned() else { return };
+        self.mode = AppMode::Normal;
+        self.switch_device_to(&query);
+    }
+
+    // Juga dipakai `:device <name>`: device pertama yang namanya mengandung `query`
+    fn switch_device_to(&mut self, query: &str) {
+        let opened = device::open(Some(query))
+            .and_then(|(stream, handle, name)| Ok((stream, Sink::try_new(&handle).map_err(|e| e.to_string())?, name)));
+        let (stream, sink, name) = match opened {
+            Ok(output) => output,
+            Err(e) => {
+                warn!("cannot switch output: {}", e);
+                self.last_error = Some(format!("Cannot switch output: {}", e));
+                return;
+            }
+        };
+
+        // 1. Fade out di device lama, lalu tukar stream & sink
+        let position = self.position_tracker.estimate();
+        let playing = self.track_was_loaded && !self.sink.empty() && !self.sink.is_paused();
+        self.finish_fade();
+        if playing {
+            let _ = fade_out(&self.sink, FADE_STEPS, FADE_STEP_MS).join();
+        }
+        self.sink.stop();
+        self.sink = Arc::new(sink);
+        self._stream = stream;
+        self.device_name = name;
+        // 2. Lanjutkan lagu sekarang dari posisi yang sama
+        self.reopen_current(position, playing);
+        self.status = Some(format!("Output: {}", self.device_name.as_deref().unwrap_or(query)));
+    }
+
+    // Putar ulang lagu sekarang di sink yang sudah kosong, mulai dari `position`
+    // (diam dulu sampai seek selesai). `playing` = lanjut dengan fade-in, selain itu tetap pause.
+    fn reopen_current(&mut self, position: Duration, playing: bool) {
+        // Lagu yang di-preload sudah ikut terbuang bersama isi sink
+        self.preload_task = None;
+        self.preload_checked = false;
+        self.preload_target = None;
+        self.preload_queued = false;
+
+        if let Some(path) = self.current_path.clone().filter(|_| self.track_was_loaded) {
+            let source = File::open(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|file| Decoder::new(BufReader::new(file)).map_err(|e| e.to_string()));
+            match source {
+                Ok(source) => {
+                    self.sink.set_volume(0.0);
+                    self.append_source(&path, source);
+                    if let Err(e) = self.sink_seek(position) {
+                        warn!("cannot resume {} at {:?}: {}", path.display(), position, e);
+                    }
+                    self.position_tracker.reset(position);
+                    if playing {
+                        self.fading = true;
+                        self.fade_task = Some(fade_in(&self.sink, self.output_volume(), FADE_STEPS, FADE_STEP_MS));
+                    } else {
+                        self.sink.pause();
+                        self.apply_volume();
+                    }
+                }
+                Err(e) => {
+                    warn!("cannot reopen {}: {}", path.display(), e);
+                    self.track_was_loaded = false;
+                }
+            }
+        } else {
+            self.apply_volume();
+        }
+    }
+
+    // `<` / `>`: tempo 0.5x - 2.0x. Faktor Speed melekat di source, jadi source dibuat ulang
+    // dari posisi sekarang dengan faktor baru
+    fn adjust_speed(&mut self, delta: f32) {
+        let speed = ((self.speed + delta).clamp(0.5, 2.0) * 10.0).round() / 10.0;
+        if speed == self.speed {
+            return;
+        }
+        let position = self.position_tracker.estimate();
+        let playing = self.track_was_loaded && !self.sink.empty() && !self.sink.is_paused();
+        self.finish_fade();
+        self.sink.stop();
+        self.speed = speed;
+        self.position_tracker.set_speed(speed);
+        self.reopen_current(position, playing);
+        self.status = Some(format!("Speed: {:.1}x", speed));
+    }
+
+    // Posisi lagu dari sink. get_pos dihitung setelah Speed (waktu jam dinding sejak seek terakhir
+    // dibagi tempo), jadi dikali `speed` supaya kembali ke waktu lagu.
+    fn sink_pos(&self) -> Duration {
+        self.sink.get_pos().mul_f32(self.speed)
+    }
+
+    // Kebalikan sink_pos: Speed mengalikan target seek dengan faktornya
+    fn sink_seek(&self, position: Duration) -> Result<(), rodio::source::SeekError> {
+        self.sink.try_seek(position.div_f32(self.speed))
+    }
+
+    // `S`: statistik seluruh library. Selama metadata belum lengkap dihitung ulang tiap tick
+    fn open_stats(&mut self) {
+        if self.stats_cache.is_none() {
+            spawn_meta_scan(self.library.iter().filter(|p| is_audio_file(p)).cloned().collect(), self.meta_cache.clone());
+            self.refresh_stats();
+        }
+        self.mode = AppMode::Stats;
+    }
+
+    fn refresh_stats(&mut self) {
+        let audio: Vec<PathBuf> = self.library.iter().filter(|p| is_audio_file(p)).cloned().collect();
+        self.stats_cache = Some(collection_stats(&audio, &self.meta_cache));
+    }
+
+    // `v`: tampilan dua panel on/off. Keluar = kembali ke seluruh library.
+    fn toggle_tree_view(&mut self) {
+        self.tree_view = !self.tree_view;
+        if self.tree_view {
+            self.focus = Focus::Tree;
+            if let Some(dir) = self.tree.selected_path() {
+                self.show_dir(&dir);
+            }
+        } else {
+            self.focus = Focus::Browser;
+            let dir = self.config.music_dir.clone();
+            self.replace_files(&dir, self.config.max_scan_depth);
+        }
+    }
+
+    // Enter / `l` di pohon: buka folder dan tampilkan isinya di panel file
+    fn open_tree_dir(&mut self) {
+        if let Some(dir) = self.tree.expand_selected() {
+            self.show_dir(&dir);
+        }
+    }
+
+    // Isi panel file dengan lagu & playlist langsung di `dir` (tanpa subfolder)
+    fn show_dir(&mut self, dir: &Path) {
+        self.replace_files(dir, 0);
+        self.tree_dir = Some(dir.to_path_buf());
+    }
+
+    // Perubahan folder musik dari watcher: file baru masuk file list, file terhapus keluar
+    fn tick_watcher(&mut self) {
+        let Some(watcher) = &self.watcher else { return };
+        let mut added = vec![];
+        let mut removed = vec![];
+        for change in watcher.changes.try_iter() {
+            match change {
+                // Folder baru (mis. album yang di-copy): isinya mungkin sudah ada sebelum folder ikut dipantau
+                LibraryChange::Added(path) if path.is_dir() => added.extend(scan_dir(&path, 0, self.config.max_scan_depth)),
+                LibraryChange::Added(path) if path.is_file() && (is_audio_file(&path) || is_playlist_file(&path)) => added.push(path),
+                LibraryChange::Added(_) => {}
+                LibraryChange::Removed(path) => removed.push(path),
+            }
+        }
+        let mut updated = !removed.is_empty() && self.remove_library_files(&removed);
+        updated |= !added.is_empty() && self.add_library_files(added);
+        if updated {
+            self.library_flash = Some(Instant::now());
+        }
+    }
+
+    // File baru masuk library, dan ke file list kalau termasuk yang sedang ditampilkan
+    fn add_library_files(&mut self, added: Vec<PathBuf>) -> bool {
+        let mut shown = false;
+        for path in added {
+            // File yang selesai ditulis: metadata yang terbaca saat file masih setengah jadi dibuang
+            if let Ok(mut cache) = self.meta_cache.lock() {
+                cache.remove(&path);
+            }
+            if !self.library.contains(&path) {
+                self.library.push(path.clone());
+            }
+            // Hanya kalau file list (tab aktif) memang hasil scan folder yang berisi file ini
+            let visible = match (&self.files_dir, self.tree_view) {
+                (None, _) => false,
+                (Some(dir), true) => path.parent() == Some(dir.as_path()),
+                (Some(dir), false) => path.starts_with(dir),
+            };
+            if visible && !self.files.contains(&path) {
+                self.files.push(path);
+                shown = true;
+            }
+        }
+        spawn_meta_scan(self.audio_files(), self.meta_cache.clone());
+        if shown {
+            self.sort_files();
+        }
+        self.stats_cache = None;
+        shown
+    }
+
+    // File (atau folder) yang dihapus keluar dari library, queue & file list.
+    // Lagu yang sedang diputar ikut terhapus: stop, lanjut ke entry sesudahnya.
+    fn remove_library_files(&mut self, removed: &[PathBuf]) -> bool {
+        let gone = |p: &Path| removed.iter().any(|r| p.starts_with(r));
+        self.library.retain(|p| !gone(p));
+        self.queue.retain(|p| !gone(p));
+        self.stats_cache = None;
+
+        let playing = self.current_path.as_deref().is_some_and(gone).then(|| self.current_index()).flatten();
+        let selected = self.file_list_state.selected().unwrap_or(0);
+        let entries: Vec<(PathBuf, Option<String>, Option<usize>)> = self.files.drain(..)
+            .enumerate()
+            .map(|(i, path)| (path, self.display_names.remove(&i), self.file_cue.remove(&i)))
+            .collect();
+        let before = entries.len();
+        let mut next = None;
+        let mut cursor = None;
+        self.display_names.clear();
+        self.file_cue.clear();
+        for (old, (path, name, cue)) in entries.into_iter().enumerate() {
+            if gone(&path) {
+                continue;
+            }
+            let i = self.files.len();
+            if next.is_none() && playing.is_some_and(|p| old > p) {
+                next = Some(i);
+            }
+            if old <= selected {
+                cursor = Some(i);
+            }
+            if let Some(name) = name {
+                self.display_names.insert(i, name);
+            }
+            if let Some(cue) = cue {
+                self.file_cue.insert(i, cue);
+            }
+            self.files.push(path);
+        }
+        self.file_list_state.select(cursor.or((!self.files.is_empty()).then_some(0)));
+        if self.shuffle {
+            self.shuffle = false;
+            self.toggle_shuffle();
+        }
+
+        if self.current_path.as_deref().is_some_and(gone) {
+            self.sink.stop();
+            self.track_was_loaded = false;
+            self.status = Some("Playing file was removed".to_string());
+            if let Some(i) = next {
+                self.play_index(i);
+            }
+        }
+        before != self.files.len()
+    }
+
+    // Isi file list dengan hasil scan `dir`, lalu sort
+    fn replace_files(&mut self, dir: &Path, max_depth: u8) {
+        self.virtual_playlist = None;
+        self.set_files(scan_dir(dir, 0, max_depth));
+        self.files_dir = Some(dir.to_path_buf());
+        self.sort_files();
+    }
+
+    // Ganti isi file list apa adanya (tanpa sort)
+    fn set_files(&mut self, files: Vec<PathBuf>) {
+        self.files = files;
+        self.files_dir = None;
+        self.display_names.clear();
+        self.expand_cue_files();
+        self.file_list_state.select((!self.files.is_empty()).then_some(0));
+        spawn_meta_scan(self.audio_files(), self.meta_cache.clone());
+    }
+
+    // File di `files` yang bisa diputar (tanpa playlist)
+    fn audio_files(&self) -> Vec<PathBuf> {
+        self.files.iter().filter(|p| is_audio_file(p)).cloned().collect()
+    }
+
+    fn cycle_sort(&mut self) {
+        self.sort_mode = self.sort_mode.next();
+        self.sort_files();
+        self.status = Some(format!("Sort: {}", self.sort_mode.label()));
+    }
+
+    // Urutkan `files` sesuai `sort_mode`. Kursor tetap di file yang sama,
+    // nama #EXTINF ikut pindah. Entry tanpa metadata ditaruh di akhir.
+    fn sort_files(&mut self) {
+        let selected = self.file_list_state.selected().and_then(|i| self.files.get(i).cloned());
+        let selected_cue = self.file_list_state.selected().and_then(|i| self.file_cue.get(&i).copied());
+        let mut entries: Vec<(PathBuf, Option<String>, Option<usize>)> = self.files.drain(..)
+            .enumerate()
+            .map(|(i, path)| (path, self.display_names.remove(&i), self.file_cue.remove(&i)))
+            .collect();
+
+        // Sort stabil: lagu-lagu CUE dari file yang sama tetap berurutan
+        let meta = self.meta_cache.lock().map(|c| c.clone()).unwrap_or_default();
+        match self.sort_mode {
+            SortMode::Name => entries.sort_by_cached_key(|(path, _, _)| folder_natural_key(path)),
+            SortMode::TrackNumber => entries.sort_by_cached_key(|(path, _, _)| {
+                let track = meta.get(path).and_then(|m| m.track_number);
+                // Track number sama (beda album) -> urut folder & nama
+                (track.is_none(), track, folder_natural_key(path))
+            }),
+            SortMode::Modified => entries.sort_by_cached_key(|(path, _, _)| {
+                let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+                (modified.is_none(), modified)
+            }),
+            SortMode::Duration => entries.sort_by_cached_key(|(path, _, _)| {
+                let duration = meta.get(path).and_then(|m| m.duration);
+                (duration.is_none(), duration)
+            }),
+            // Rating tertinggi di atas, lagu tanpa rating paling bawah
+            SortMode::Rating => entries.sort_by_cached_key(|(path, _, _)| {
+                let rating = self.ratings.get(path).copied();
+                (rating.is_none(), std::cmp::Reverse(rating))
+            }),
+            SortMode::Plays => entries.sort_by_cached_key(|(path, _, _)| {
+                std::cmp::Reverse(self.play_counts.get(path).copied().unwrap_or(0))
+            }),
+        }
+
+        self.display_names.clear();
+        self.file_cue.clear();
+        for (i, (path, name, cue)) in entries.into_iter().enumerate() {
+            if let Some(name) = name {
+                self.display_names.insert(i, name);
+            }
+            if let Some(cue) = cue {
+                self.file_cue.insert(i, cue);
+            }
+            self.files.push(path);
+        }
+        let idx = selected.and_then(|p| {
+            (0..self.files.len()).find(|&i| self.files[i] == p && self.file_cue.get(&i).copied() == selected_cue)
+        });
+        self.file_list_state.select(idx.or((!self.files.is_empty()).then_some(0)));
+
+        // Urutan shuffle lama tidak berlaku untuk urutan baru
+        if self.shuffle {
+            self.shuffle = false;
+            self.toggle_shuffle();
+        }
+    }
+
+    // Nama yang ditampilkan di file list: nama #EXTINF atau nama file
+    fn file_display_name(&self, idx: usize) -> String {
+        match self.display_names.get(&idx) {
+            Some(name) => name.clone(),
+            None => display_file_name(&self.files[idx]),
+        }
+    }
+
+    // Hitung ulang `filtered_files` dari query (fuzzy, skor tertinggi di atas)
+    fn update_search(&mut self) {
+        let AppMode::Search(query) = &self.mode else { return };
+        let mut scored: Vec<(u32, usize)> = (0..self.files.len())
+            .filter_map(|i| fuzzy_score(query, &self.file_display_name(i)).map(|score| (score, i)))
+            .collect();
+        // Sort stabil: skor sama tetap urut seperti daftar asli
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.truncate(SEARCH_MAX_RESULTS);
+        self.filtered_files = scored.into_iter().map(|(_, i)| i).collect();
+        self.file_list_state.select((!self.filtered_files.is_empty()).then_some(0));
+    }
+
+    // Keluar dari pencarian; kursor kembali ke daftar penuh di file yang dipilih
+    fn exit_search(&mut self) -> Option<usize> {
+        let selected = self.file_list_state.selected().and_then(|i| self.filtered_files.get(i).copied());
+        self.mode = AppMode::Normal;
+        self.filtered_files.clear();
+        self.file_list_state.select(selected.or((!self.files.is_empty()).then_some(0)));
+        selected
+    }
+
+    // Jalankan perintah dari prompt `:` (tanpa titik dua)
+    fn run_command(&mut self, input: &str) {
+        let input = input.trim();
+        let (cmd, arg) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        match (cmd, arg.trim()) {
+            ("", _) => {}
+            ("w", "") => self.last_error = Some("Usage: :w <path>".to_string()),
+            ("w", path) => self.write_playlist(Path::new(path)),
+            ("t", minutes) => self.set_sleep_timer(minutes),
+            ("sort", name) => match SortMode::from_name(name) {
+                Some(mode) => {
+                    self.sort_mode = mode;
+                    self.sort_files();
+                    self.status = Some(format!("Sort: {}", mode.label()));
+                }
+                None => self.last_error = Some("Usage: :sort name|track|modified|duration|rating|plays".to_string()),
+            },
+            ("theme", name) => self.set_theme(name),
+            ("device", "") => self.last_error = Some("Usage: :device <name>".to_string()),
+            ("device", query) => self.switch_device_to(query),
+            ("goto" | "seek", time) => self.goto_time(cmd == "seek", time),
+            // `:q` ditangani langsung di run_app (keluar dari loop)
+            ("q", _) => {}
+            _ => self.last_error = Some(format!("Unknown command: {}", cmd)),
+        }
+    }
+
+    // `:goto <time>` / `:seek <time>`; `:seek` juga menerima +/- untuk lompat relatif
+    fn goto_time(&mut self, relative: bool, time: &str) {
+        let (sign, time) = match time.as_bytes().first() {
+            Some(b'+') if relative => (1.0, &time[1..]),
+            Some(b'-') if relative => (-1.0, &time[1..]),
+            _ => (0.0, time),
+        };
+        let Some(target) = parse_time(time) else {
+            self.last_error = Some("Invalid time format".to_string());
+            return;
+        };
+        if sign != 0.0 {
+            self.seek_by(sign * target.as_secs_f64());
+        } else {
+            // Lagu CUE: waktu dihitung dari awal lagu virtual, sama seperti progress bar
+            let offset = self.cue_span().map_or(Duration::ZERO, |(start, _)| start);
+            self.seek_to((offset + target).as_secs_f64());
+        }
+    }
+
+    // Nama perintah yang cocok dengan kata pertama di prompt, skor fuzzy tertinggi dulu.
+    // Sudah mengetik argumen (ada spasi): tidak ada completion lagi.
+    fn command_completions(input: &str) -> Vec<&'static str> {
+        if input.contains(char::is_whitespace) {
+            return vec![];
+        }
+        if input.is_empty() {
+            return COMMANDS.iter().map(|&(name, _, _)| name).collect();
+        }
+        let mut scored: Vec<(u32, &'static str)> = COMMANDS
+            .iter()
+            .filter_map(|&(name, _, _)| Some((fuzzy_score(input, name)?, name)))
+            .collect();
+        // Skor sama: nama yang lebih pendek (lebih tepat) dulu
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.len().cmp(&b.1.len())));
+        scored.into_iter().map(|(_, name)| name).collect()
+    }
+
+    // Enter di prompt: completion yang dipilih, atau teks yang diketik apa adanya
+    fn resolve_command(&self, input: &str) -> String {
+        let completions = Self::command_completions(input.trim());
+        match completions.get(self.command_completion) {
+            Some(name) if !input.trim().is_empty() => name.to_string(),
+            _ => input.to_string(),
+        }
+    }
+
+    // `:theme <name>`: ganti skema warna tanpa restart (tidak disimpan ke config)
+    fn set_theme(&mut self, name: &str) {
+        let previous = std::mem::replace(&mut self.config.theme.name, name.to_string());
+        match self.config.theme() {
+            Ok(theme) => {
+                self.theme = theme;
+                self.status = Some(format!("Theme: {}", name));
+            }
+            Err(e) => {
+                self.config.theme.name = previous;
+                self.last_error = Some(format!("Error: {}", e));
+            }
+        }
+    }
+
+    // `:t <menit>` — kosong atau 0 membatalkan timer
+    fn set_sleep_timer(&mut self, minutes: &str) {
+        let parsed = if minutes.is_empty() { Ok(0) } else { minutes.parse::<u64>() };
+        match parsed {
+            Ok(0) => {
+                if self.sleep_timer.take().is_some() {
+                    self.status = Some("Sleep timer cancelled".to_string());
+                }
+            }
+            Ok(minutes) if let Some(deadline) = Instant::now().checked_add(Duration::from_secs(minutes.saturating_mul(60))) => {
+                self.sleep_timer = Some(deadline);
+                self.status = Some(format!("Sleep timer: {} min", minutes));
+            }
+            _ => self.last_error = Some(format!("Invalid sleep timer minutes: {}", minutes)),
+        }
+    }
+
+    // Simpan `files` sebagai playlist M3U8. Hanya file audio (playlist bersarang tidak ikut);
+    // nama dari metadata cache, tidak pernah baca tag di sini (UI thread)
+    fn write_playlist(&mut self, path: &Path) {
+        let mut content = String::from("#EXTM3U\n");
+        let mut written = 0;
+        {
+            let meta = self.meta_cache.lock().ok();
+            let cached = |file: &PathBuf| meta.as_ref().and_then(|m| m.get(file));
+            for (i, file) in self.files.iter().enumerate().filter(|(_, f)| is_audio_file(f)) {
+                let name = if self.current_path.as_ref() == Some(file) {
+                    format!("{} - {}", self.title, self.artist)
+                } else if let Some(name) = self.display_names.get(&i) {
+                    name.clone()
+                } else if let Some(title) = cached(file).and_then(|m| m.title.clone()) {
+                    match cached(file).and_then(|m| m.artist.as_ref()) {
+                        Some(artist) => format!("{} - {}", title, artist),
+                        None => title,
+                    }
+                } else {
+                    file.file_stem().unwrap_or_default().to_string_lossy().to_string()
+                };
+                let abs = fs::canonicalize(file).unwrap_or_else(|_| file.clone());
+                content.push_str(&format!("#EXTINF:-1,{}\n{}\n", name, abs.display()));
+                written += 1;
+            }
+        }
+        match write_atomic(path, &content) {
+            Ok(()) => self.status = Some(format!("Wrote {} tracks to {}", written, path.display())),
+            Err(e) => {
+                error!("failed to write playlist {}: {}", path.display(), e);
+                self.last_error = Some(format!("Failed to write {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    // Scroll lirik manual sebanyak `delta` baris (nilai besar = lompat ke ujung)
+    fn scroll_lyrics(&mut self, delta: isize) {
+        let visible = self.lyrics.iter().filter(|l| self.is_lyric_visible(l)).count();
+        if visible == 0 {
+            return;
+        }
+        let current = self.lyrics_state.selected().unwrap_or(0) as isize;
+        let target = (current + delta).clamp(0, visible as isize - 1) as usize;
+        self.lyrics_state.select(Some(target));
+        self.lyrics_manual_scroll = true;
+        self.lyrics_last_scroll = Instant::now();
+    }
+
+    // `/` di panel lirik: buka kotak pencarian di bawah panel
+    fn start_lyrics_search(&mut self) {
+        self.lyrics_query = Some(String::new());
+        self.mode = AppMode::LyricsSearch;
+    }
+
+    // Index (di antara baris yang tampil) semua baris lirik yang cocok dengan query
+    fn lyric_matches(&self) -> Vec<usize> {
+        let Some(re) = self.lyrics_query.as_deref().and_then(lyrics::search_regex) else { return vec![] };
+        self.lyrics
+            .iter()
+            .filter(|l| self.is_lyric_visible(l))
+            .enumerate()
+            .filter(|(_, l)| re.is_match(&l.text))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // 0 = hasil pertama (saat mengetik), 1 / -1 = `n` / `N` dari baris yang dipilih, berputar
+    fn jump_lyric_match(&mut self, direction: isize) {
+        let matches = self.lyric_matches();
+        let current = self.lyrics_state.selected();
+        let target = match (direction, current) {
+            (1, Some(c)) => matches.iter().find(|&&i| i > c).or(matches.first()),
+            (-1, Some(c)) => matches.iter().rev().find(|&&i| i < c).or(matches.last()),
+            _ => matches.first(),
+        };
+        let Some(&target) = target else {
+            if self.lyrics_query.as_ref().is_some_and(|q| !q.is_empty()) {
+                self.status = Some("No match in lyrics".to_string());
+            }
+            return;
+        };
+        self.status = None;
+        self.lyrics_state.select(Some(target));
+        self.lyrics_manual_scroll = true;
+        self.lyrics_last_scroll = Instant::now();
+    }
+
+    // Esc: tutup pencarian, lirik kembali mengikuti lagu
+    fn close_lyrics_search(&mut self) {
+        self.lyrics_query = None;
+        self.lyrics_manual_scroll = false;
+        if self.mode == AppMode::LyricsSearch {
+            self.mode = AppMode::Normal;
+        }
+    }
+
+    // Simpan lirik yang sedang dimuat ke file .lrc di sebelah lagu (tombol x)
+    fn export_lyrics(&mut self) {
+        let Some(path) = self.current_path.clone() else { return };
+        if self.lyrics.is_empty() {
+            self.status = Some("No lyrics to export".to_string());
+            return;
+        }
+        let lrc_path = path.with_extension("lrc");
+        if lrc_path.exists() {
+            self.confirm = Some(ConfirmAction::OverwriteLrc(lrc_path));
+        } else {
+            self.write_lyrics(&lrc_path);
+        }
+    }
+
+    fn write_lyrics(&mut self, lrc_path: &Path) {
+        let mut content = String::new();
+        for (tag, value) in [("ti", &self.title), ("ar", &self.artist), ("al", &self.album)] {
+            if !value.is_empty() && value != "-" && !value.starts_with("Unknown ") {
+                content.push_str(&format!("[{}:{}]\n", tag, value));
+            }
+        }
+        for line in &self.lyrics {
+            content.push_str(&format_lrc_line(line));
+            content.push('\n');
+        }
+        match write_atomic(lrc_path, &content) {
+            Ok(()) => {
+                self.last_error = None;
+                self.status = Some(format!("Lyrics saved to {}", lrc_path.display()));
+            }
+            Err(e) => {
+                error!("failed to write lyrics {}: {}", lrc_path.display(), e);
+                self.last_error = Some(format!("Failed to write {}: {}", lrc_path.display(), e));
+            }
+        }
+    }
+
+    // `W`: simpan cover sebagai PNG di cover_save_dir
+    fn save_cover(&mut self) {
+        let Some(img) = self.cover_data.as_deref().and_then(thumb_cache::decode) else {
+            self.status = Some("No cover art to save".to_string());
+            return;
+        };
+        // Karakter yang tidak aman untuk nama file diganti '_'
+        let name: String = self.title.chars()
+            .map(|c| if c.is_control() || matches!(c, '/' | '\\' | ':') { '_' } else { c })
+            .collect();
+        let path = self.config.cover_save_dir.join(format!("punini-cover-{}.png", name.trim()));
+        let result = fs::create_dir_all(&self.config.cover_save_dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| img.save_with_format(&path, ImageFormat::Png).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => {
+                self.last_error = None;
+                self.status = Some(format!("Cover saved to {}", path.display()));
+            }
+            Err(e) => {
+                warn!("cannot save cover to {}: {}", path.display(), e);
+                self.last_error = Some(format!("Cannot save cover: {}", e));
+            }
+        }
+    }
+
+    // Isi awal input saat field tag mulai diedit
+    fn tag_field_value(&self, field: TagField) -> String {
+        match field {
+            TagField::Title => self.title.clone(),
+            TagField::Artist => self.artist.clone(),
+            TagField::Album => self.album.clone(),
+            TagField::Year => self.year.map(|y| y.to_string()).unwrap_or_default(),
+        }
+    }
+
+    fn start_tag_edit(&mut self, field: TagField) {
+        if !self.meta_ready {
+            self.last_error = Some("Error: no track playing".to_string());
+            return;
+        }
+        let input = self.tag_field_value(field);
+        let cursor = input.chars().count();
+        self.mode = AppMode::TagEdit { field, input, cursor };
+    }
+
+    // Enter di mode edit tag: tulis ke file, lalu perbarui tampilan
+    fn save_tag_field(&mut self, field: TagField, input: &str) {
+        let Some(path) = self.current_path.clone() else { return };
+        let value = input.trim();
+        match write_tag_field(&path, field, value) {
+            Ok(()) => {
+                match field {
+                    TagField::Title => self.title = value.to_string(),
+                    TagField::Artist => self.artist = value.to_string(),
+                    TagField::Album => self.album = value.to_string(),
+                    TagField::Year => self.year = value.parse().ok(),
+                }
+                // File list & pencarian memakai cache metadata
+                if let Ok(mut cache) = self.meta_cache.lock()
+                    && let Some(entry) = cache.get_mut(&path)
+                {
+                    match field {
+                        TagField::Title => entry.title = Some(value.to_string()).filter(|v| !v.is_empty()),
+                        TagField::Artist => entry.artist = Some(value.to_string()).filter(|v| !v.is_empty()),
+                        TagField::Album | TagField::Year => {}
+                    }
+                }
+                self.last_error = None;
+                self.status = Some("Saved".to_string());
+            }
+            Err(e) => {
+                warn!("cannot write tag to {}: {}", path.display(), e);
+                self.last_error = Some(format!("Error: {}", e));
+            }
+        }
+    }
+
+    // Jawaban y/n untuk `confirm`
+    fn resolve_confirm(&mut self, accepted: bool) {
+        let Some(action) = self.confirm.take() else { return };
+        match action {
+            ConfirmAction::OverwriteLrc(lrc_path) if accepted => self.write_lyrics(&lrc_path),
+            ConfirmAction::OverwriteLrc(_) => self.status = Some("Export cancelled".to_string()),
+        }
+    }
+
+    // Baris kosong tetap disimpan (untuk timing), tapi bisa disembunyikan dari tampilan
+    fn is_lyric_visible(&self, line: &LyricLine) -> bool {
+        !(self.config.lrc_hide_blank_lines && line.text.is_empty())
+    }
+
+    // Ubah index di `lyrics` menjadi index di daftar yang ditampilkan
+    fn visible_lyric_index(&self, idx: usize) -> Option<usize> {
+        let line = self.lyrics.get(idx)?;
+        if !self.is_lyric_visible(line) {
+            return None;
+        }
+        Some(self.lyrics[..idx].iter().filter(|l| self.is_lyric_visible(l)).count())
+    }
+
+    // Kursor file list, berputar di ujung daftar
+    fn move_file_cursor(&mut self, delta: isize) {
+        if self.files.is_empty() {
+            return;
+        }
+        let len = self.files.len() as isize;
+        let i = self.file_list_state.selected().map_or(0, |i| (i as isize + delta).rem_euclid(len));
+        self.file_list_state.select(Some(i as usize));
+    }
+
+    // `K` / `c`: buka tampilan layar penuh, atau kembali ke layout biasa kalau sudah di sana.
+    // Karaoke dan cover tidak bisa bersamaan; dari yang satu langsung pindah ke yang lain.
+    fn toggle_display_mode(&mut self, mode: DisplayMode) {
+        self.display_mode = if self.display_mode == mode { DisplayMode::Normal } else { mode };
+    }
+
+    fn toggle_high_contrast(&mut self) {
+        self.high_contrast = !self.high_contrast;
+        self.config.high_contrast = self.high_contrast;
+        match config::save_value("high_contrast", &self.high_contrast.to_string()) {
+            Ok(()) => self.status = Some(format!("High contrast: {}", if self.high_contrast { "on" } else { "off" })),
+            Err(e) => self.last_error = Some(format!("Error: {}", e)),
+        }
+    }
+
+    fn toggle_accessibility_mode(&mut self) {
+        self.config.accessibility_mode = !self.config.accessibility_mode;
+        let on = self.config.accessibility_mode;
+        match config::save_value("accessibility_mode", &on.to_string()) {
+            Ok(()) => self.status = Some(format!("Accessibility mode: {}", if on { "on" } else { "off" })),
+            Err(e) => self.last_error = Some(format!("Error: {}", e)),
+        }
+    }
+
+    // --- Logic Sinkronisasi Lirik ---
+    fn sync_lyrics(&mut self) {
+        // Scroll manual kembali ke auto-sync setelah 5 detik tanpa input
+        // (kecuali selama pencarian lirik masih terbuka)
+        if self.lyrics_manual_scroll
+            && self.lyrics_query.is_none()
+            && self.lyrics_last_scroll.elapsed() >= LYRICS_MANUAL_TIMEOUT
+        {
+            self.lyrics_manual_scroll = false;
+        }
+        let current_pos = self.position_tracker.estimate();
+        if !self.lyrics.is_empty() && !self.lyrics_manual_scroll {
+            let active_idx = self.lyrics.iter().rposition(|line| line.time <= current_pos);
+            // Kalau baris aktif adalah spacer kosong, tidak ada yang di-highlight
+            let display_idx = active_idx.and_then(|i| self.visible_lyric_index(i));
+            self.lyrics_state.select(display_idx);
+        }
+    }
+}
+
+// Kunci urut "Name": folder dulu (per album), lalu natural_sort_key nama file
+fn folder_natural_key(path: &Path) -> (Option<PathBuf>, NaturalKey) {
+    (path.parent().map(Path::to_path_buf), natural_sort_key(path))
+}
+
+// Handler sinyal hanya men-set flag (async-signal-safe); run_app yang keluar
+fn register_shutdown_signals() -> Arc<AtomicBool> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGINT] {
+        if let Err(e) = signal_hook::flag::register(signal, shutdown.clone()) {
+            warn!("cannot register handler for signal {}: {}", signal, e);
+        }
+    }
+    shutdown
+}
+
+// Error fatal sebelum TUI jalan: dicatat ke log dan tetap ditampilkan di stderr (masih kelihatan)
+fn exit_with_error(msg: &str, code: i32) -> ! {
+    error!("{}", msg);
+    eprintln!("Error: {}", msg);
+    std::process::exit(code);
+}
+
+fn main() -> Result<()> {
+    // 0. Argumen CLI dulu (butuh --log-level), lalu config file (CLI > env > config file > default)
+    let args = cli::Args::parse();
+    if let Some(shell) = args.generate_completions {
+        cli::print_completions(shell);
+        return Ok(());
+    }
+    let startup_action = if args.restore {
+        Some(StartupAction::RestoreSession)
+    } else if args.autoplay {
+        Some(StartupAction::AutoPlay)
+    } else {
+        None
+    };
+    let log_level = args.log_level;
+    let print_config = args.print_config;
+    let list_themes = args.list_themes;
+    let test_borders = args.test_borders;
+    let print_paths_flag = args.print_paths;
+    let clear_cache = args.clear_cache;
+    let print_keybindings = args.print_keybindings;
+    let theme_flag = args.theme;
+    let no_restore = args.no_restore;
+    let headless = args.headless;
+    let volume_flag = args.volume;
+    let socket_flag = args.socket;
+    // Nama sudah dicek clap (possible values)
+    let sort_flag = args.sort.as_deref().and_then(SortMode::from_name);
+    // Angka setelahnya opsional
+    let most_played_flag = args.most_played.map(|n| n.unwrap_or(VIRTUAL_PLAYLIST_LEN));
+    let check_duplicates = args.check_duplicates;
+    let near_duplicates = args.near;
+    // Path setelahnya opsional (default: folder musik)
+    let verify_flag = args.verify;
+    let no_color = args.no_color;
+    let device_flag = args.device;
+    let dir_flag = args.dir;
+    let dir_positional = args.dir_positional;
+
+    if let Err(e) = logging::init(&log_level) {
+        exit_with_error(&e, 2);
+    }
+    info!("punini {} starting", env!("CARGO_PKG_VERSION"));
+    if list_themes {
+        for name in theme::names() {
+            println!("{}", name);
+        }
+        return Ok(());
+    }
+    if test_borders {
+        print_border_test();
+        return Ok(());
+    }
+    if clear_cache {
+        match thumb_cache::clear() {
+            Ok(dir) => println!("removed {}", dir.display()),
+            Err(e) => exit_with_error(&e, 1),
+        }
+        return Ok(());
+    }
+    if print_paths_flag {
+        print_paths(socket_flag.clone().or_else(dirs::socket_path).as_deref());
+        return Ok(());
+    }
+    // Sebelum config & audio: cukup isi KEYBINDINGS, tanpa device maupun folder musik
+    if print_keybindings {
+        print!("{}", keybindings_markdown());
+        return Ok(());
+    }
+
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => exit_with_error(&e, 1),
+    };
+    if let Some(action) = startup_action {
+        config.startup_action = action;
+    }
+    if let Some(volume) = volume_flag {
+        config.volume = volume;
+    }
+    if no_restore && config.startup_action == StartupAction::RestoreSession {
+        config.startup_action = StartupAction::ShowBrowser;
+    }
+    if let Some(name) = theme_flag {
+        config.theme.name = name;
+    }
+    let theme = match config.theme() {
+        Ok(theme) => theme,
+        Err(e) => exit_with_error(&e, 2),
+    };
+
+    // Prioritas folder musik: --dir > argumen pertama > $PUNINI_MUSIC_DIR > config
+    if let Some(dir) = dir_flag
+        .or(dir_positional)
+        .or_else(|| std::env::var_os("PUNINI_MUSIC_DIR").map(PathBuf::from))
+    {
+        config.music_dir = dir;
+    }
+    if print_config {
+        print!("{}", config.to_toml());
+        return Ok(());
+    }
+    if let Some(target) = verify_flag {
+        let target = target.unwrap_or_else(|| config.music_dir.clone());
+        let files = if target.is_dir() {
+            scan_dir(&target, 0, config.max_scan_depth).into_iter().filter(|p| verify::is_flac(p)).collect()
+        } else if target.is_file() {
+            vec![target]
+        } else {
+            exit_with_error(&format!("not found: {}", target.display()), 1);
+        };
+        let ok = verify::run(files, verify::color_enabled(no_color));
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+    // Headless juga menerima satu file lagu atau playlist, bukan hanya folder
+    let single_file = headless && config.music_dir.is_file();
+    if !single_file && !config.music_dir.is_dir() {
+        exit_with_error(&format!("music directory not found: {}", config.music_dir.display()), 1);
+    }
+    // Mode laporan: tidak butuh audio device maupun TUI
+    if check_duplicates {
+        let files = scan_dir(&config.music_dir, 0, config.max_scan_depth).into_iter().filter(|p| is_audio_file(p)).collect();
+        duplicates::run(files, near_duplicates);
+        return Ok(());
+    }
+
+    // 1. Setup Audio
+    // Tanpa --device: device default, daftar device lain cukup di log
+    if device_flag.is_none() {
+        info!("audio output devices: {}", device::names().join(", "));
+    }
+    let (_stream, stream_handle, device_name) = match device::open(device_flag.as_deref()) {
+        Ok(output) => output,
+        Err(e) if device_flag.is_some() => {
+            exit_with_error(&format!("{} (available: {})", e, device::names().join(", ")), 1)
+        }
+        Err(e) => exit_with_error(&format!("no audio device: {}", e), 1),
+    };
+    let sink = Sink::try_new(&stream_handle).context("Failed to create sink")?;
+
+    // 2. Scan Folder Musik
+    let files = if single_file {
+        headless::files_from(&config.music_dir)
+    } else {
+        let mut files = scan_with_progress(&config.music_dir, config.max_scan_depth);
+        // Urutkan per folder album, lalu nama file secara alami (2 sebelum 10)
+        files.sort_by_cached_key(|path| folder_natural_key(path));
+        files
+    };
+
+    // --most-played: file list = N lagu yang paling sering diputar dari hasil scan
+    let play_counts: HashMap<PathBuf, u32> = store::load(PLAYCOUNTS_FILE);
+    let library = files.clone();
+    let files = match most_played_flag {
+        Some(n) => most_played(files, &play_counts, n),
+        None => files,
+    };
+
+    // Volume awal dari config; state & sink selalu sama
+    sink.set_volume(config.volume);
+    let volume = sink.volume();
+
+    let (worker_tx, worker_rx) = worker::spawn();
+
+    let shutdown = register_shutdown_signals();
+
+    let position_resync_ms = config.position_resync_ms;
+    let high_contrast = config.high_contrast;
+    let music_dir = config.music_dir.clone();
+
+    // Remote control: --socket > $XDG_RUNTIME_DIR/punini.sock
+    let socket_path = socket_flag.or_else(dirs::socket_path);
+
+    // 3. Init State (Kosong dulu)
+    let mut app = AppState {
+        config,
+        sink: Arc::new(sink),
+        position_tracker: PositionTracker::new(Duration::from_millis(position_resync_ms)),
+        _stream,
+        device_name,
+        current_path: None,
+        volume,
+        shuffle: false,
+        shuffle_order: vec![],
+        history: vec![],
+        repeat: RepeatMode::Off,
+        track_was_loaded: false,
+        play_counted: false,
+        speed: 1.0,
+        rg_enabled: false,
+        rg_mode: RgMode::Track,
+        replaygain: ReplayGain::default(),
+        current_format: None,
+        fading: false,
+        fade_task: None,
+        preload_task: None,
+        preload_checked: false,
+        preload_target: None,
+        preload_queued: false,
+        preload_duration: Duration::from_secs(0),
+        title: "No Track Playing".to_string(),
+        artist: "".to_string(),
+        album: "".to_string(),
+        duration: Duration::from_secs(0),
+        cover_art: None,
+        cover_data: None,
+        chapters: vec![],
+        year: None,
+        genre: None,
+        composer: None,
+        comment: None,
+        tag_fields: vec![],
+        bitrate: None,
+        sample_rate: None,
+        channels: None,
+        file_size: 0,
+        playback_unsupported: false,
+        worker_tx,
+        worker_rx,
+        meta_ready: false,
+        headless,
+        lyrics: vec![],
+        lyrics_state: ListState::default(),
+        lyrics_manual_scroll: false,
+        lyrics_query: None,
+        lyrics_last_scroll: Instant::now(),
+
+        files,
+        file_list_state: ListState::default(),
+        display_names: HashMap::new(),
+        bookmarks: store::load(BOOKMARKS_FILE),
+        ratings: store::load(RATINGS_FILE),
+        play_counts,
+        bookmarks_state: ListState::default(),
+        library,
+        virtual_playlist: None,
+        files_dir: most_played_flag.is_none().then(|| music_dir.clone()),
+        playlists_state: ListState::default(),
+        stats_cache: None,
+        watcher: None,
+        library_flash: None,
+        tree_dir: None,
+        devices: vec![],
+        devices_state: ListState::default(),
+        tree: DirTree::new(music_dir.clone()),
+        tree_view: false,
+        file_cue: HashMap::new(),
+        cue_tracks: HashMap::new(),
+        current_cue: None,
+        sort_mode: SortMode::Name,
+        meta_cache: MetaCache::default(),
+        focus: Focus::Browser,
+        queue: VecDeque::new(),
+        queue_state: ListState::default(),
+        filtered_files: vec![],
+
+        last_error: None,
+        status: None,
+        confirm: None,
+
+        mode: AppMode::Normal,
+        command_completion: 0,
+        display_mode: DisplayMode::Normal,
+        high_contrast,
+        theme,
+
+        tabs: vec![PlaylistTab::new(display_file_name(&music_dir))],
+        active_tab: 0,
+        playing_tab: None,
+        sleep_timer: None,
+        window_title: None,
+        progress_area: Rect::default(),
+        player_area: Rect::default(),
+        volume_overlay: None,
+        title_scroll_offset: 0,
+        title_scroll_at: Instant::now(),
+        shutdown,
+        remote: socket_path.as_deref().and_then(remote::spawn),
+
+        #[cfg(feature = "mpris")]
+        mpris: mpris::spawn(),
+        #[cfg(feature = "mpris")]
+        mpris_path: None,
+        #[cfg(feature = "notify")]
+        notification_handle: None,
+    };
+
+    app.expand_cue_files();
+    spawn_meta_scan(app.audio_files(), app.meta_cache.clone());
+    if let Some(mode) = sort_flag {
+        app.sort_mode = mode;
+        app.sort_files();
+    }
+
+    // Pilih file pertama secara default (tapi belum di-load/play)
+    if !app.files.is_empty() {
+        app.file_list_state.select(Some(0));
+    }
+
+    if app.headless {
+        return headless::run(&mut app);
+    }
+    app.watcher = watch::spawn(&app.config.music_dir);
+
+    let mut pending_resume = None;
+    match app.config.startup_action {
+        StartupAction::ShowBrowser => {}
+        StartupAction::AutoPlay => {
+            if let Some(first) = app.files.first().cloned() {
+                app.load_track(&first);
+            } else {
+                app.title = "[No audio files found in configured directories]".to_string();
+            }
+        }
+        // Ada queue tersimpan: tanya dulu lewat prompt setelah terminal siap
+        StartupAction::RestoreSession => {
+            let queue = app.load_saved_queue();
+            match app.saved_session() {
+                Some(saved) if !queue.is_empty() => pending_resume = Some((saved, queue)),
+                Some(saved) => app.resume_session(&saved, saved.position()),
+                None => app.queue = queue,
+            }
+        }
+    }
+
+    // 4. UI Loop
+    enable_raw_mode()?;
+    let mut stdout = stdout();
+    if app.config.update_title {
+        // Simpan judul asli di title stack xterm (CSI 22 t), dipulihkan saat keluar
+        let _ = stdout.write_all(b"\x1b[22;0t");
+    }
+    execute!(stdout, EnterAlternateScreen, EnableFocusChange)?;
+    if app.config.mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    if let Some((saved, queue)) = pending_resume {
+        let question = resume_queue_question(&saved, queue.len());
+        match prompt_resume_queue(&mut terminal, &question)? {
+            Some(from_position) => {
+                app.queue = queue;
+                let position = if from_position { saved.position() } else { Duration::ZERO };
+                app.resume_session(&saved, position);
+            }
+            None => info!("saved queue ignored"),
+        }
+    }
+
+    let res = run_app(&mut terminal, &mut app);
+    // Watcher dihentikan dulu, tidak perlu event lagi selama terminal dipulihkan
+    app.watcher = None;
+    if res.is_ok() {
+        app.save_session();
+    }
+
+    disable_raw_mode()?;
+    if app.config.mouse {
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+    }
+    execute!(io::stdout(), DisableFocusChange, LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    if app.config.update_title {
+        // Terminal tanpa title stack setidaknya tidak menyisakan judul lagu
+        let _ = execute!(io::stdout(), SetTitle(""));
+        let _ = io::stdout().write_all(b"\x1b[23;0t");
+        let _ = io::stdout().flush();
+    }
+
+    if let Err(err) = res {
+        error!("{:?}", err);
+        eprintln!("Error: {:?}", err);
+    }
+
+    Ok(())
+}
+
+fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.as_str()))
+}
+
+// `ss`, `m:ss`, `mm:ss`, `hh:mm:ss` -> Duration. Menit / detik setelah ':' harus < 60.
+// Field terakhir boleh diberi pecahan detik: `83.5`, `1:23.250`
+fn parse_time(text: &str) -> Option<Duration> {
+    let is_number = |p: &str| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit());
+    let (text, fraction) = match text.trim().split_once('.') {
+        Some((text, fraction)) if is_number(fraction) => (text, fraction),
+        Some(_) => return None,
+        None => (text.trim(), ""),
+    };
+    let parts: Vec<&str> = text.split(':').collect();
+    if parts.len() > 3 || !parts.iter().all(|p| is_number(p)) {
+        return None;
+    }
+    // Lebih dari 9 digit (di bawah nanodetik) dipotong
+    let nanos = if fraction.is_empty() { 0 } else { format!("{:0<9.9}", fraction).parse().ok()? };
+    let mut secs: u64 = 0;
+    for (i, part) in parts.iter().enumerate() {
+        let value: u64 = part.parse().ok()?;
+        if i > 0 && value >= 60 {
+            return None;
+        }
+        secs = secs.checked_mul(60)?.checked_add(value)?;
+    }
+    Some(Duration::new(secs, nanos))
+}
+
+// Pindahkan entry `from` ke posisi `to`; entry di antaranya bergeser satu.
+// Tetangga cukup ditukar (O(1)), selain itu rotate bagian di antaranya (O(n))
+fn move_entry<T>(items: &mut VecDeque<T>, from: usize, to: usize) {
+    if from.abs_diff(to) == 1 {
+        items.swap(from, to);
+    } else if to < from {
+        items.make_contiguous()[to..=from].rotate_right(1);
+    } else if from < to {
+        items.make_contiguous()[from..=to].rotate_left(1);
+    }
+}
+
+// Map per index `files` setelah entry `removed` dibuang: index sesudahnya mundur satu
+fn shift_indices_after<V>(map: &mut HashMap<usize, V>, removed: usize) {
+    *map = std::mem::take(map).into_iter().map(|(i, v)| (if i > removed { i - 1 } else { i }, v)).collect();
+}
+
+// Nama file untuk ditampilkan. Nama dari HFS+ (macOS) berbentuk NFD dan tampil rusak
+// di terminal Linux, jadi dinormalisasi ke NFC.
+fn display_file_name(path: &Path) -> String {
+    nfc(&path.file_name().unwrap_or_default().to_string_lossy())
+}
+
+fn nfc(s: &str) -> String {
+    s.nfc().collect()
+}
+
+// Awal `s` yang muat di `width` kolom terminal; karakter CJK lebarnya 2 kolom
+fn truncate_to_width(s: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in s.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+fn is_playlist_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| PLAYLIST_EXTENSIONS.contains(&ext.as_str()))
+}
+
+// Parse isi M3U: baris non-# adalah path (absolut atau relatif ke folder playlist),
+// `#EXTINF:<durasi>,<nama>` memberi nama untuk entry berikutnya.
+// Mengembalikan (entry valid, path yang tidak ditemukan)
+fn parse_m3u(content: &str, base_dir: &Path) -> (Vec<(PathBuf, Option<String>)>, Vec<String>) {
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    let mut pending_name = None;
+
+    for line in content.trim_start_matches('\u{feff}').lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(info) = line.strip_prefix('#') {
+            if let Some(info) = info.strip_prefix("EXTINF:") {
+                pending_name = info.split_once(',')
+                    .map(|(_, name)| name.trim().to_string())
+                    .filter(|name| !name.is_empty());
+            }
+            continue;
+        }
+
+        let name = pending_name.take();
+        let path = base_dir.join(line);
+        if path.is_file() && is_audio_file(&path) {
+            entries.push((path, name));
+        } else {
+            skipped.push(line.to_string());
+        }
+    }
+    (entries, skipped)
+}
+
+// Identitas folder untuk deteksi loop symlink
+#[cfg(unix)]
+type DirKey = (u64, u64);
+#[cfg(not(unix))]
+type DirKey = PathBuf;
+
+#[cfg(unix)]
+fn dir_key(path: &Path) -> Option<DirKey> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn dir_key(path: &Path) -> Option<DirKey> {
+    fs::canonicalize(path).ok()
+}
+
+// Scan folder secara rekursif (ikut symlink) sampai `max_depth` level
+fn scan_dir(path: &Path, depth: u8, max_depth: u8) -> Vec<PathBuf> {
+    scan_dir_counted(path, depth, max_depth, &AtomicUsize::new(0))
+}
+
+// Scan paralel (rayon). `found` naik setiap file ketemu, untuk indikator progres.
+// Urutan hasil rayon tidak tetap, jadi diurutkan dulu.
+fn scan_dir_counted(path: &Path, depth: u8, max_depth: u8, found: &AtomicUsize) -> Vec<PathBuf> {
+    let visited = Mutex::new(HashSet::new());
+    let mut files = scan_dir_into(path, depth, max_depth, &visited, found);
+    files.sort_unstable();
+    files
+}
+
+fn scan_dir_into(path: &Path, depth: u8, max_depth: u8, visited: &Mutex<HashSet<DirKey>>, found: &AtomicUsize) -> Vec<PathBuf> {
+    // Folder yang sudah pernah dikunjungi (lewat symlink) dilewati
+    match dir_key(path) {
+        Some(key) if visited.lock().unwrap_or_else(|e| e.into_inner()).insert(key) => {}
+        _ => return vec![],
+    }
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            debug!("cannot scan {}: {}", path.display(), e);
+            return vec![];
+        }
+    };
+
+    entries.flatten().par_bridge().flat_map_iter(|entry| {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth < max_depth {
+                return scan_dir_into(&path, depth + 1, max_depth, visited, found);
+            }
+        } else if path.is_file() && (is_audio_file(&path) || is_playlist_file(&path)) {
+            found.fetch_add(1, Ordering::Relaxed);
+            return vec![path];
+        }
+        vec![]
+    }).collect()
+}
+
+// Scan awal sebelum TUI jalan: "Scanning… N files found" di layar biasa, dihapus setelah selesai
+fn scan_with_progress(path: &Path, max_depth: u8) -> Vec<PathBuf> {
+    if !io::stdout().is_terminal() {
+        return scan_dir(path, 0, max_depth);
+    }
+    let found = AtomicUsize::new(0);
+    let done = AtomicBool::new(false);
+    let files = thread::scope(|s| {
+        s.spawn(|| {
+            while !done.load(Ordering::Relaxed) {
+                print!("Scanning… {} files found\r", found.load(Ordering::Relaxed));
+                let _ = io::stdout().flush();
+                thread::sleep(Duration::from_millis(100));
+            }
+        });
+        let files = scan_dir_counted(path, 0, max_depth, &found);
+        done.store(true, Ordering::Relaxed);
+        files
+    });
+    print!("\r\x1b[2K");
+    let _ = io::stdout().flush();
+    files
+}
+
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut AppState) -> Result<()> {
+    let mut last_render: Option<Instant> = None;
+    let mut focused = true;
+    let mut last_input = Instant::now();
+    let mut paused_since: Option<Instant> = None;
+    loop {
+        // Dibunuh lewat kill / SIGINT: keluar lewat jalur cleanup biasa
+        if app.shutdown.load(Ordering::Relaxed) {
+            info!("received termination signal, shutting down");
+            return Ok(());
+        }
+
+        // Gambar ulang paling cepat tiap render_interval_ms, terlepas dari seberapa sering input dicek.
+        // Terminal di belakang / lagu di-pause lama tanpa input: cukup sesekali.
+        let paused = app.track_was_loaded && app.sink.is_paused();
+        paused_since = if paused { paused_since.or(Some(Instant::now())) } else { None };
+        let idle = paused_since.is_some_and(|t| t.elapsed() >= IDLE_AFTER) && last_input.elapsed() >= IDLE_AFTER;
+        let render_interval = if !focused {
+            UNFOCUSED_RENDER_INTERVAL
+        } else if idle {
+            IDLE_RENDER_INTERVAL
+        } else {
+            Duration::from_millis(app.config.render_interval_ms)
+        };
+        if last_render.is_none_or(|t| t.elapsed() >= render_interval) {
+            let theme = app.theme;
+            terminal.draw(|f| ui(f, app, &theme))?;
+            last_render = Some(Instant::now());
+        }
+        app.update_window_title();
+
+        app.tick();
+        app.tick_watcher();
+
+        // Lirik tetap disinkronkan tiap putaran, walau render sedang diperlambat
+        app.sync_lyrics();
+
+        // --- Event Handling ---
+        // Semua input yang sudah antri diproses sekaligus (tanpa menunggu), baru tidur sebentar.
+        // `continue` di bawah = lanjut ke event berikutnya.
+        while event::poll(Duration::ZERO)? {
+            let ev = event::read()?;
+            match ev {
+                Event::FocusLost => focused = false,
+                Event::FocusGained => focused = true,
+                Event::Key(_) | Event::Mouse(_) => last_input = Instant::now(),
+                _ => {}
+            }
+            if let Event::Mouse(mouse) = ev {
+                app.on_mouse(mouse);
+            }
+            if let Event::Key(key) = ev
+                && key.kind == KeyEventKind::Press
+            {
+                // Selama ada pertanyaan y/n, tombol lain diabaikan
+                if app.confirm.is_some() {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') => app.resolve_confirm(true),
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.resolve_confirm(false),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Mode perintah: semua tombol navigasi dimatikan, hanya edit teks
+                if let AppMode::Command(input) = &mut app.mode {
+                    match key.code {
+                        KeyCode::Esc => app.mode = AppMode::Normal,
+                        KeyCode::Enter => {
+                            let input = std::mem::take(input);
+                            let command = app.resolve_command(&input);
+                            app.mode = AppMode::Normal;
+                            app.command_completion = 0;
+                            if command.trim() == "q" {
+                                return Ok(());
+                            }
+                            app.run_command(&command);
+                        }
+                        // Tab: completion berikutnya
+                        KeyCode::Tab => {
+                            let count = AppState::command_completions(input.trim()).len();
+                            if count > 0 {
+                                app.command_completion = (app.command_completion + 1) % count;
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            input.pop();
+                            app.command_completion = 0;
+                        }
+                        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            input.clear();
+                            app.command_completion = 0;
+                        }
+                        KeyCode::Char(c) => {
+                            input.push(c);
+                            app.command_completion = 0;
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Mode pencarian: ketikan masuk ke query, panah untuk memilih hasil
+                if let AppMode::Search(query) = &mut app.mode {
+                    match key.code {
+                        KeyCode::Esc => {
+                            app.exit_search();
+                        }
+                        KeyCode::Enter => {
+                            if let Some(idx) = app.exit_search() {
+                                app.play_index(idx);
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            query.pop();
+                            app.update_search();
+                        }
+                        KeyCode::Char(c) => {
+                            query.push(c);
+                            app.update_search();
+                        }
+                        KeyCode::Up | KeyCode::Down if !app.filtered_files.is_empty() => {
+                            let len = app.filtered_files.len();
+                            let i = app.file_list_state.selected().unwrap_or(0);
+                            let i = if key.code == KeyCode::Up { (i + len - 1) % len } else { (i + 1) % len };
+                            app.file_list_state.select(Some(i));
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Pencarian lirik: ketikan masuk ke query, lirik langsung lompat ke baris pertama yang cocok
+                if app.mode == AppMode::LyricsSearch {
+                    let query = app.lyrics_query.get_or_insert_default();
+                    match key.code {
+                        KeyCode::Esc => app.close_lyrics_search(),
+                        // Query tetap disorot; n / N untuk hasil berikutnya
+                        KeyCode::Enter => app.mode = AppMode::Normal,
+                        KeyCode::Backspace => {
+                            query.pop();
+                            app.jump_lyric_match(0);
+                        }
+                        KeyCode::Char(c) => {
+                            query.push(c);
+                            app.jump_lyric_match(0);
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Edit tag: teks biasa, Up/Down/Tab pindah field
+                if let AppMode::TagEdit { field, input, cursor } = &mut app.mode {
+                    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+                    let byte_at = |s: &str, i: usize| s.char_indices().nth(i).map_or(s.len(), |(b, _)| b);
+                    match key.code {
+                        KeyCode::Esc => app.mode = AppMode::Normal,
+                        KeyCode::Enter => {
+                            let (field, input) = (*field, std::mem::take(input));
+                            app.mode = AppMode::Normal;
+                            app.save_tag_field(field, &input);
+                        }
+                        KeyCode::Up | KeyCode::BackTab => {
+                            let prev = field.prev();
+                            app.start_tag_edit(prev);
+                        }
+                        KeyCode::Down | KeyCode::Tab => {
+                            let next = field.next();
+                            app.start_tag_edit(next);
+                        }
+                        KeyCode::Char('a') if ctrl => *cursor = 0,
+                        KeyCode::Char('e') if ctrl => *cursor = input.chars().count(),
+                        KeyCode::Char('u') if ctrl => {
+                            input.clear();
+                            *cursor = 0;
+                        }
+                        KeyCode::Home => *cursor = 0,
+                        KeyCode::End => *cursor = input.chars().count(),
+                        KeyCode::Left => *cursor = cursor.saturating_sub(1),
+                        KeyCode::Right => *cursor = (*cursor + 1).min(input.chars().count()),
+                        KeyCode::Backspace if *cursor > 0 => {
+                            *cursor -= 1;
+                            let at = byte_at(input, *cursor);
+                            input.remove(at);
+                        }
+                        KeyCode::Char(c) if !ctrl => {
+                            let at = byte_at(input, *cursor);
+                            input.insert(at, c);
+                            *cursor += 1;
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Overlay bookmark: navigasi, Enter putar, d hapus
+                if app.mode == AppMode::Bookmarks {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('B') | KeyCode::Char('q') => app.mode = AppMode::Normal,
+                        KeyCode::Up | KeyCode::Char('k') => app.move_bookmark_cursor(-1),
+                        KeyCode::Down | KeyCode::Char('j') => app.move_bookmark_cursor(1),
+                        KeyCode::Enter => app.play_bookmark(),
+                        KeyCode::Char('d') => app.remove_selected_bookmark(),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Menu output audio: navigasi, Enter pindah device
+                if app.mode == AppMode::Devices {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('D') | KeyCode::Char('q') => app.mode = AppMode::Normal,
+                        KeyCode::Up | KeyCode::Char('k') => app.move_device_cursor(-1),
+                        KeyCode::Down | KeyCode::Char('j') => app.move_device_cursor(1),
+                        KeyCode::Enter => app.switch_device(),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Layar statistik: tombol apa saja kembali ke tampilan biasa
+                if app.mode == AppMode::Stats {
+                    app.mode = AppMode::Normal;
+                    continue;
+                }
+
+                // Menu playlist virtual: navigasi, Enter buka
+                if app.mode == AppMode::VirtualPlaylists {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('P') | KeyCode::Char('q') => app.mode = AppMode::Normal,
+                        KeyCode::Up | KeyCode::Char('k') => app.move_playlist_cursor(-1),
+                        KeyCode::Down | KeyCode::Char('j') => app.move_playlist_cursor(1),
+                        KeyCode::Enter => app.open_selected_playlist(),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Popup tag: hanya scroll & tutup
+                if let AppMode::TagInfo(scroll) = &mut app.mode {
+                    let max = app.tag_fields.len().saturating_sub(1) as u16;
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('i') | KeyCode::Char('q') => app.mode = AppMode::Normal,
+                        KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                        KeyCode::Down | KeyCode::Char('j') => *scroll = (*scroll + 1).min(max),
+                        KeyCode::PageUp => *scroll = scroll.saturating_sub(LYRICS_PAGE as u16),
+                        KeyCode::PageDown => *scroll = (*scroll + LYRICS_PAGE as u16).min(max),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Overlay bantuan: hanya scroll & tutup
+                if let AppMode::Help(scroll) = &mut app.mode {
+                    // Baris tombol + judul & baris kosong per bagian
+                    let max = (KEYBINDINGS.len() + 2 * KeybindingSection::ALL.len()).saturating_sub(1) as u16;
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => app.mode = AppMode::Normal,
+                        KeyCode::Up => *scroll = scroll.saturating_sub(1),
+                        KeyCode::Down => *scroll = (*scroll + 1).min(max),
+                        KeyCode::PageUp => *scroll = scroll.saturating_sub(LYRICS_PAGE as u16),
+                        KeyCode::PageDown => *scroll = (*scroll + LYRICS_PAGE as u16).min(max),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Tombol yang diganti lewat [keys] diterjemahkan ke tombol default-nya
+                match app.config.remap(key.code) {
+                    KeyCode::Char('q') => return Ok(()),
+                    // Di raw mode Ctrl-C tidak jadi SIGINT, jadi ditangani sebagai tombol
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+                    KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => app.new_tab(),
+                    KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => app.close_tab(),
+                    KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => app.toggle_accessibility_mode(),
+                    // Angka tanpa Alt tetap untuk rating
+                    KeyCode::Char(c @ '1'..='9') if key.modifiers.contains(KeyModifiers::ALT) => app.select_tab((c as u8 - b'1') as usize),
+                    KeyCode::Char('M') if app.focus == Focus::Browser => app.move_selected_to_next_tab(),
+                    KeyCode::Char('/') if app.focus == Focus::Lyrics => app.start_lyrics_search(),
+                    KeyCode::Char('n') if app.focus == Focus::Lyrics && app.lyrics_query.is_some() => app.jump_lyric_match(1),
+                    KeyCode::Char('N') if app.focus == Focus::Lyrics && app.lyrics_query.is_some() => app.jump_lyric_match(-1),
+                    KeyCode::Esc if app.lyrics_query.is_some() => app.close_lyrics_search(),
+                    KeyCode::Char('/') => {
+                        app.focus = Focus::Browser;
+                        app.mode = AppMode::Search(String::new());
+                        app.update_search();
+                    }
+                    KeyCode::Char(':') => {
+                        app.last_error = None;
+                        app.mode = AppMode::Command(String::new());
+                    }
+                    KeyCode::Char('i') => app.mode = AppMode::TagInfo(0),
+                    KeyCode::Char('?') => app.mode = AppMode::Help(0),
+                    KeyCode::Char('e') => app.start_tag_edit(TagField::Title),
+                    KeyCode::Char('W') => app.save_cover(),
+                    KeyCode::Char('b') => app.bookmark_selected(),
+                    KeyCode::Char(c @ '0'..='5') => app.rate_current(c as u8 - b'0'),
+                    KeyCode::Char('B') => app.open_bookmarks(),
+                    KeyCode::Char('P') => app.open_playlist_menu(),
+                    KeyCode::Char('S') => app.open_stats(),
+                    KeyCode::Char('D') => app.open_devices(),
+                    KeyCode::Char('K') => app.toggle_display_mode(DisplayMode::Karaoke),
+                    KeyCode::Char('c') => app.toggle_display_mode(DisplayMode::Cover),
+                    KeyCode::Char('m') => app.toggle_display_mode(DisplayMode::Mini),
+                    KeyCode::Char('H') => app.toggle_high_contrast(),
+                    KeyCode::Char('<') => app.adjust_speed(-0.1),
+                    KeyCode::Char('>') => app.adjust_speed(0.1),
+                    KeyCode::Esc if app.virtual_playlist.is_some() => app.close_virtual_playlist(),
+                    // Sleep timer: prompt perintah yang sudah terisi `t `
+                    KeyCode::Char('t') => {
+                        app.last_error = None;
+                        app.mode = AppMode::Command("t ".to_string());
+                    }
+
+                    // Play / Pause
+                    KeyCode::Char(' ') => app.toggle_pause(),
+
+                    // Next / Previous track & Shuffle
+                    KeyCode::Char('n') => app.next_track(),
+                    KeyCode::Char('N') => app.prev_track(),
+                    KeyCode::Char('s') => app.toggle_shuffle(),
+                    KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => app.toggle_rg_mode(),
+                    KeyCode::Char('r') => app.repeat = app.repeat.next(),
+                    KeyCode::Char('R') => app.toggle_replaygain(),
+                    KeyCode::Char('o') => app.cycle_sort(),
+                    KeyCode::Char('p') | KeyCode::Backspace => app.history_back(),
+
+                    // Volume
+                    KeyCode::Char('[') => app.adjust_volume(-0.05),
+                    KeyCode::Char(']') => app.adjust_volume(0.05),
+
+                    // Seek (Shift = lompat lebih jauh)
+                    KeyCode::Left => {
+                        let step = if key.modifiers.contains(KeyModifiers::SHIFT) { 30.0 } else { 5.0 };
+                        app.seek_by(-step);
+                    }
+                    KeyCode::Right => {
+                        let step = if key.modifiers.contains(KeyModifiers::SHIFT) { 30.0 } else { 5.0 };
+                        app.seek_by(step);
+                    }
+                    KeyCode::Char('{') => app.seek_chapter(false),
+                    KeyCode::Char('}') => app.seek_chapter(true),
+
+                    // Queue: a = tambah di akhir, A = putar berikutnya, Q/Tab = pindah panel
+                    KeyCode::Char('a') => app.enqueue_selected(false),
+                    KeyCode::Char('A') => app.enqueue_selected(true),
+                    KeyCode::Char('Q') if app.focus == Focus::Browser => app.toggle_focus(),
+                    KeyCode::Tab => app.toggle_focus(),
+
+                    // Tampilan dua panel (pohon folder + file)
+                    KeyCode::Char('v') => app.toggle_tree_view(),
+                    KeyCode::Up | KeyCode::Char('k') if app.focus == Focus::Tree => app.tree.move_cursor(-1),
+                    KeyCode::Down | KeyCode::Char('j') if app.focus == Focus::Tree => app.tree.move_cursor(1),
+                    KeyCode::Enter | KeyCode::Char('l') if app.focus == Focus::Tree => app.open_tree_dir(),
+                    KeyCode::Char('h') if app.focus == Focus::Tree => app.tree.collapse_selected(),
+
+                    // Panel queue aktif: navigasi, d & Enter bekerja di queue
+                    KeyCode::Up if app.focus == Focus::Queue && key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.move_queue_entry(-1)
+                    }
+                    KeyCode::Down if app.focus == Focus::Queue && key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.move_queue_entry(1)
+                    }
+                    KeyCode::Home if app.focus == Focus::Queue && key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.move_queue_entry(isize::MIN)
+                    }
+                    KeyCode::End if app.focus == Focus::Queue && key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.move_queue_entry(isize::MAX)
+                    }
+                    KeyCode::Up | KeyCode::Char('k') if app.focus == Focus::Queue => app.move_queue_cursor(-1),
+                    KeyCode::Down | KeyCode::Char('j') if app.focus == Focus::Queue => app.move_queue_cursor(1),
+                    KeyCode::Char('d') if app.focus == Focus::Queue => app.remove_selected_from_queue(),
+
+                    // Panel lirik aktif: j/k scroll per baris
+                    KeyCode::Up | KeyCode::Char('k') if app.focus == Focus::Lyrics => app.scroll_lyrics(-1),
+                    KeyCode::Down | KeyCode::Char('j') if app.focus == Focus::Lyrics => app.scroll_lyrics(1),
+
+                    // Navigasi File (Atas/Bawah/j/k)
+                    KeyCode::Up | KeyCode::Char('k') => app.move_file_cursor(-1),
+                    KeyCode::Down | KeyCode::Char('j') => app.move_file_cursor(1),
+
+                    // Export lirik ke .lrc
+                    KeyCode::Char('x') => app.export_lyrics(),
+
+                    // Scroll Lirik Manual
+                    KeyCode::PageUp => app.scroll_lyrics(-LYRICS_PAGE),
+                    KeyCode::PageDown => app.scroll_lyrics(LYRICS_PAGE),
+                    KeyCode::Home => app.scroll_lyrics(isize::MIN / 2),
+                    KeyCode::End => app.scroll_lyrics(isize::MAX / 2),
+
+                    // Enter saat scroll manual = kembali ke auto-sync
+                    KeyCode::Enter if app.lyrics_manual_scroll => app.lyrics_manual_scroll = false,
+
+                    KeyCode::Enter if app.focus == Focus::Queue => app.play_from_queue(),
+
+                    // Play Selected File (Enter), atau buka playlist
+                    KeyCode::Enter => {
+                        if let Some(i) = app.file_list_state.selected()
+                            && let Some(path) = app.files.get(i)
+                        {
+                            // Cloning path karena load_track butuh &Path dan app dipinjam mut
+                            let path_clone = path.clone();
+                            if is_playlist_file(&path_clone) {
+                                app.load_playlist(&path_clone);
+                            } else {
+                                // play_index, bukan load_track: entry CUE perlu seek ke lagunya
+                                app.play_index(i);
+                            }
+                        }
+                    }
+
+                    _ => {}
+                }
+            }
+        }
+
+        thread::sleep(Duration::from_millis(app.config.poll_interval_ms));
+    }
+}
+
+fn ui(f: &mut Frame, app: &mut AppState, colors: &Theme) {
+    render_screen(f, app, colors);
+
+    // Kontras tinggi: warna diganti per sel setelah semua panel digambar
+    if app.high_contrast {
+        for cell in f.buffer_mut().content.iter_mut() {
+            let style = apply_theme(cell.style(), app);
+            cell.set_style(style);
+            // Karakter box-drawing = border panel
+            if cell.symbol().chars().next().is_some_and(|c| ('\u{2500}'..='\u{257f}').contains(&c)) {
+                cell.set_fg(Color::White);
+            }
+        }
+    }
+}
+
+// Block dengan border penuh; jenis garisnya dari config `border_style`
+const ASCII_BORDER: symbols::border::Set = symbols::border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+fn bordered(style: BorderStyle) -> Block<'static> {
+    let block = Block::default().borders(Borders::ALL);
+    match style {
+        BorderStyle::Plain => block.border_type(BorderType::Plain),
+        BorderStyle::Rounded => block.border_type(BorderType::Rounded),
+        BorderStyle::Double => block.border_type(BorderType::Double),
+        BorderStyle::Thick => block.border_type(BorderType::Thick),
+        BorderStyle::QuadrantInside => block.border_type(BorderType::QuadrantInside),
+        BorderStyle::QuadrantOutside => block.border_type(BorderType::QuadrantOutside),
+        BorderStyle::Ascii => block.border_set(ASCII_BORDER),
+    }
+}
+
+// --print-keybindings: tabel Markdown per bagian, untuk README
+fn keybindings_markdown() -> String {
+    let mut out = String::new();
+    for section in KeybindingSection::ALL {
+        out.push_str(&format!("## {}\n\n| Key | Description |\n| --- | --- |\n", section.label()));
+        for (_, key, about) in KEYBINDINGS.iter().filter(|(s, _, _)| *s == section) {
+            out.push_str(&format!("| `{}` | {} |\n", key.replace('|', "\\|"), about.replace('|', "\\|")));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// --print-paths: semua lokasi file punini setelah env XDG dibaca
+fn print_paths(socket: Option<&Path>) {
+    let state_dir = dirs::xdg_state_dir();
+    let paths = [
+        ("config", config::config_path()),
+        ("session", session::session_path()),
+        ("queue", session::queue_path()),
+        ("ratings", store::path(RATINGS_FILE)),
+        ("bookmarks", store::path(BOOKMARKS_FILE)),
+        ("playcounts", store::path(PLAYCOUNTS_FILE)),
+        ("log", state_dir.join(format!("{}.*", logging::LOG_FILE_PREFIX))),
+        ("cover cache", thumb_cache::dir()),
+    ];
+    for (name, path) in paths {
+        println!("{:<12} {}", name, path.display());
+    }
+    match socket {
+        Some(socket) => println!("{:<12} {}", "socket", socket.display()),
+        None => println!("{:<12} (XDG_RUNTIME_DIR is not set, use --socket)", "socket"),
+    }
+}
+
+// --test-borders: semua jenis border digambar ke buffer lalu dicetak ke stdout (layar biasa,
+// bukan alternate screen), supaya bisa dipilih yang rapi di font terminal sendiri
+fn print_border_test() {
+    const BOX_WIDTH: u16 = 20;
+    const BOX_HEIGHT: u16 = 3;
+    for style in BorderStyle::ALL {
+        let area = Rect::new(0, 0, BOX_WIDTH, BOX_HEIGHT);
+        let mut buffer = ratatui::buffer::Buffer::empty(area);
+        bordered(style).title(format!(" {} ", style.name())).render(area, &mut buffer);
+        for y in 0..BOX_HEIGHT {
+            let row: String = (0..BOX_WIDTH).map(|x| buffer[(x, y)].symbol()).collect();
+            println!("{}", row);
+        }
+    }
+    println!("\nSet one of these as border_style in config.toml, e.g. border_style = \"rounded\"");
+}
+
+// Ganti warna yang sulit dibaca saat high_contrast aktif
+fn apply_theme(style: Style, state: &AppState) -> Style {
+    if !state.high_contrast {
+        return style;
+    }
+    let colors = &state.theme;
+    let mut style = match style.fg {
+        Some(fg) if fg == colors.time_dim || fg == colors.progress_fill => style.fg(Color::White),
+        Some(fg) if fg == colors.metadata_secondary => style.fg(Color::Yellow),
+        _ => style,
+    };
+    // Highlight lirik & daftar file: latar kuning, teks hitam
+    match style.bg {
+        Some(bg) if bg == colors.accent || bg == colors.highlight_bg => style = style.bg(Color::Yellow).fg(Color::Black),
+        // Isi progress bar
+        Some(bg) if bg == colors.progress_fill => style = style.bg(Color::White).fg(Color::Black),
+        _ => {}
+    }
+    style
+}
+
+fn render_screen(f: &mut Frame, app: &mut AppState, colors: &Theme) {
+    let a11y = app.config.accessibility_mode.then(AccessibilityTheme::default);
+
+    // Layar statistik menggantikan seluruh layout
+    if app.mode == AppMode::Stats {
+        render_stats(f, app, colors);
+        return;
+    }
+    if f.area().width < MINI_PLAYER_BELOW {
+        return render_mini(f, app, colors);
+    }
+    match app.display_mode {
+        DisplayMode::Karaoke => return render_karaoke(f, app, colors),
+        DisplayMode::Cover => return render_cover(f, app, colors),
+        DisplayMode::Mini => return render_mini(f, app, colors),
+        DisplayMode::Normal => {}
+    }
+
+    // 0. Status bar satu baris di paling bawah
+    let screen = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(f.area());
+
+    // 1. Layout Utama: Kiri (Files) - Kanan (Player), lebarnya mengikuti terminal
+    let (files_width, player_width) = layout::compute_layout(f.area());
+    let main_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([files_width, player_width])
+        .split(screen[0]);
+
+    // --- PANEL KIRI: FILE LIST / QUEUE ---
+    // Lebih dari satu tab: baris tab di atas panel kiri
+    let left_panel = if app.tabs.len() > 1 {
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(main_chunks[0]);
+        render_tabs(f, app, parts[0], colors);
+        parts[1]
+    } else {
+        main_chunks[0]
+    };
+    match app.focus {
+        Focus::Queue => render_queue(f, app, left_panel, colors, &a11y),
+        _ if app.tree_view => {
+            let panes = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .split(left_panel);
+            render_dir_tree(f, app, panes[0], colors, &a11y);
+            render_file_list(f, app, panes[1], colors, &a11y);
+        }
+        _ => render_file_list(f, app, left_panel, colors, &a11y),
+    }
+
+    // --- PANEL KANAN: PLAYER ---
+    app.player_area = main_chunks[1];
+    // Bagi panel kanan: Vertikal (Body & Progress)
+    let right_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(main_chunks[1]);
+
+    // Bagi Body: Kiri (Cover) - Kanan (Meta & Lyrics). Terminal sempit: tanpa cover.
+    let (cover_panel, meta_panel) = match layout::cover_layout(f.area()) {
+        Some((cover_width, meta_width)) => {
+            let player_body = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([cover_width, meta_width])
+                .split(right_chunks[0]);
+            (Some(player_body[0]), player_body[1])
+        }
+        None => (None, right_chunks[0]),
+    };
+
+    // Isi panel Info dibuat dulu supaya tingginya bisa mengikuti jumlah baris
+    let repeat_tag = match app.repeat {
+        RepeatMode::Off => "",
+        RepeatMode::All => " [RA]",
+        RepeatMode::One => " [R1]",
+    };
+    // Mode edit tag: field yang dipilih diganti input dengan kursor blok
+    let editing = match &app.mode {
+        AppMode::TagEdit { field, input, cursor } => Some((*field, input.as_str(), *cursor)),
+        _ => None,
+    };
+    let field_spans = |field: TagField, value: String, style: Style| -> Vec<Span<'static>> {
+        match editing {
+            Some((f, input, cursor)) if f == field => {
+                let at = input.char_indices().nth(cursor).map_or(input.len(), |(b, _)| b);
+                let (before, rest) = input.split_at(at);
+                let mut chars = rest.chars();
+                let under = chars.next().map_or(" ".to_string(), |c| c.to_string());
+                let edit_style = Style::default().add_modifier(Modifier::UNDERLINED);
+                vec![
+                    Span::styled(before.to_string(), edit_style),
+                    Span::styled(under, edit_style.add_modifier(Modifier::REVERSED)),
+                    Span::styled(chars.as_str().to_string(), edit_style),
+                ]
+            }
+            _ => vec![Span::styled(value, style)],
+        }
+    };
+    let row = |label: &'static str, mut spans: Vec<Span<'static>>| {
+        spans.insert(0, Span::raw(label));
+        Line::from(spans)
+    };
+    // Judul lebih lebar dari panel (border + padding + label): marquee
+    let title_width = meta_panel.width.saturating_sub(4 + 8 + repeat_tag.len() as u16) as usize;
+    let title = if app.config.scroll_titles && app.title.width() > title_width {
+        if app.title_scroll_at.elapsed() >= TITLE_SCROLL_STEP {
+            app.title_scroll_offset += 1;
+            app.title_scroll_at = Instant::now();
+        }
+        let looped = format!("{}{}", app.title, TITLE_SCROLL_GAP);
+        let count = looped.chars().count();
+        let rotated: String = looped.chars().cycle().skip(app.title_scroll_offset % count).take(count).collect();
+        truncate_to_width(&rotated, title_width).to_string()
+    } else {
+        app.title_scroll_offset = 0;
+        app.title.clone()
+    };
+    let mut title_spans = field_spans(TagField::Title, title, Style::default().add_modifier(Modifier::BOLD).fg(colors.metadata_title));
+    title_spans.push(Span::styled(repeat_tag, Style::default().fg(colors.time_dim)));
+    let mut info_text = vec![
+        row("Title : ", title_spans),
+        row("Artist: ", field_spans(TagField::Artist, app.artist.clone(), Style::default().add_modifier(Modifier::BOLD))),
+        row("Album : ", field_spans(TagField::Album, app.album.clone(), Style::default().fg(colors.metadata_secondary))),
+    ];
+    if app.meta_ready
+        && let Some(path) = &app.current_path
+    {
+        let stars = app.ratings.get(path).copied().unwrap_or(0);
+        info_text.push(row("Rating: ", vec![Span::styled(rating_stars(stars), Style::default().fg(Color::Yellow))]));
+        let plays = app.play_counts.get(path).copied().unwrap_or(0);
+        info_text.push(row("Plays : ", vec![Span::styled(plays.to_string(), Style::default().fg(colors.metadata_secondary))]));
+        if let Some((gain, _)) = app.rg_gain() {
+            let text = format!("{:+.1} dB ({})", gain, app.rg_mode.label());
+            info_text.push(row("RG    : ", vec![Span::styled(text, Style::default().fg(colors.metadata_secondary))]));
+        }
+    }
+    // Field opsional: baris dilewati kalau kosong (Year tetap tampil saat sedang diedit)
+    let year = app.year.map(|y| y.to_string()).or_else(|| editing.map(|_| String::new()));
+    if let Some(year) = year {
+        info_text.push(row("Year  : ", field_spans(TagField::Year, year, Style::default().fg(colors.metadata_secondary))));
+    }
+    let optional_rows = [
+        ("Genre : ", app.genre.clone()),
+        ("Composer: ", app.composer.clone()),
+        ("Comment: ", app.comment.clone()),
+    ];
+    for (label, value) in optional_rows {
+        if let Some(value) = value {
+            info_text.push(row(label, vec![Span::styled(value, Style::default().fg(colors.metadata_secondary))]));
+        }
+    }
+    // Baris teknis: "320 kbps · 44100 Hz · 2ch · 8.4 MB"
+    if app.meta_ready {
+        let mut tech = vec![
+            app.bitrate.map_or("—".to_string(), |b| format!("{} kbps", b)),
+            app.sample_rate.map_or("—".to_string(), |r| format!("{} Hz", r)),
+            app.channels.map_or("—".to_string(), |c| format!("{}ch", c)),
+            if app.file_size > 0 { format!("{:.1} MB", app.file_size as f64 / (1024.0 * 1024.0)) } else { "—".to_string() },
+        ];
+        // Tempo ikut menggeser pitch: "320 kbps · +2.3st · ..."
+        if let Some(pitch) = semitone_label(app.speed) {
+            tech.insert(1, pitch);
+        }
+        let tech = tech.join(" · ");
+        info_text.push(Line::from(Span::styled(tech, Style::default().fg(colors.time_dim))));
+    }
+    if app.playback_unsupported {
+        info_text.push(Line::from(Span::styled("Unsupported on this platform", Style::default().fg(Color::Red))));
+    }
+    let current_pos = app.position_tracker.estimate();
+    if let Some(idx) = app.chapters.iter().rposition(|c| c.start <= current_pos) {
+        let chapter = format!("{} ({}/{})", app.chapters[idx].title, idx + 1, app.chapters.len());
+        info_text.push(Line::from(vec![Span::raw("Chapter: "), Span::styled(chapter, Style::default().fg(colors.metadata_secondary))]));
+    }
+    // +2 border, +2 padding
+    let info_height = info_text.len() as u16 + 4;
+
+    // Bagian Kanan (Meta & Lyrics)
+    let meta_lyrics = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(info_height), Constraint::Min(0)])
+        .split(meta_panel);
+
+    // 1. Cover Art
+    if let Some(cover_panel) = cover_panel {
+        let mut block_cover = bordered(app.config.border_style).title(" Art ");
+        block_cover = match &a11y {
+            Some(theme) => {
+                let playing = !app.sink.empty() && !app.sink.is_paused();
+                block_cover.border_type(if playing { theme.border_playing } else { theme.border_idle })
+            }
+            None => block_cover.fg(colors.accent),
+        };
+        let cover_area = block_cover.inner(cover_panel);
+        f.render_widget(block_cover, cover_panel);
+
+        if let Some(protocol) = &mut app.cover_art {
+            let image = StatefulImage::new(None).resize(Resize::Fit(None));
+            f.render_stateful_widget(image, cover_area, protocol);
+        }
+    }
+
+    // 2. Metadata
+    let block_info = bordered(app.config.border_style).border_style(Style::default().fg(colors.border_inactive)).title(" Info ").padding(Padding::new(1,1,1,1));
+    f.render_widget(Paragraph::new(info_text).block(block_info), meta_lyrics[0]);
+
+    // Overlay volume di tengah panel Info
+    if app.volume_overlay.is_some_and(|at| at.elapsed() < VOLUME_OVERLAY_TIMEOUT) {
+        let text = format!(" Volume: {}% ", (app.volume * 100.0).round() as u32);
+        let info = meta_lyrics[0];
+        let width = (text.width() as u16 + 2).min(info.width);
+        let height = 3.min(info.height);
+        let overlay = Rect::new(
+            info.x + (info.width - width) / 2,
+            info.y + (info.height - height) / 2,
+            width,
+            height,
+        );
+        let style = match &a11y {
+            Some(_) => Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            None => Style::default().fg(Color::Black).bg(colors.accent).add_modifier(Modifier::BOLD),
+        };
+        f.render_widget(Clear, overlay);
+        f.render_widget(
+            Paragraph::new(text).alignment(Alignment::Center).style(style).block(bordered(app.config.border_style)),
+            overlay,
+        );
+    } else {
+        app.volume_overlay = None;
+    }
+
+    // 3. Lyrics
+    let hidden = app.lyrics.iter().filter(|l| !app.is_lyric_visible(l)).count();
+    let lyrics_title = if hidden > 0 {
+        format!(" Lyrics [{} blank lines hidden] ", hidden)
+    } else {
+        " Lyrics ".to_string()
+    };
+    let lyrics_title = if app.lyrics_manual_scroll {
+        format!("{}[Manual] ", lyrics_title)
+    } else {
+        lyrics_title
+    };
+    let mut block_lyrics = bordered(app.config.border_style).border_style(Style::default().fg(colors.border_inactive)).title(lyrics_title);
+    if app.focus == Focus::Lyrics {
+        block_lyrics = block_lyrics.border_style(active_border_style(colors, &a11y));
+    }
+    // Kotak pencarian lirik di border bawah panel
+    if let Some(query) = &app.lyrics_query {
+        let count = app.lyric_matches().len();
+        block_lyrics = block_lyrics.title_bottom(format!(" /{} ({} found) ", query, count));
+        if app.mode == AppMode::LyricsSearch {
+            let area = meta_lyrics[1];
+            let cursor_x = area.x + 3 + query.width() as u16;
+            f.set_cursor_position((cursor_x.min(area.right().saturating_sub(2)), area.bottom().saturating_sub(1)));
+        }
+    }
+    let search_re = app.lyrics_query.as_deref().and_then(lyrics::search_regex);
+    let search_highlight = match &a11y {
+        Some(_) => Style::default().add_modifier(Modifier::UNDERLINED | Modifier::BOLD),
+        None => Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+    };
+    if app.lyrics.is_empty() {
+        f.render_widget(Paragraph::new("No lyrics.").block(block_lyrics).alignment(Alignment::Center), meta_lyrics[1]);
+    } else {
+        let active = app.lyrics_state.selected();
+        let word_highlight = match &a11y {
+            Some(_) => Style::default().add_modifier(Modifier::REVERSED),
+            None => Style::default().fg(Color::Red),
+        };
+        // Lebar isi baris: tanpa border dan highlight symbol ">> "
+        let lyric_width = meta_lyrics[1].width.saturating_sub(2 + 3) as usize;
+        let items: Vec<ListItem> = app.lyrics.iter().filter(|l| app.is_lyric_visible(l)).enumerate().map(|(idx, line)| {
+            let time_str = format!("[{:02}:{:02}] ", line.time.as_secs()/60, line.time.as_secs()%60);
+            let mut spans = vec![Span::styled(time_str, Style::default().fg(colors.time_dim))];
+            // Mode aksesibilitas: baris aktif juga ditandai **teks**, bukan hanya lewat style
+            let emphasize = a11y.is_some() && active == Some(idx);
+            // Baris kanan-ke-kiri: dibalik ke urutan tampil lalu rata kanan
+            if lyrics::is_rtl(&line.text) {
+                let mut text = lyrics::visual_order(&line.text).into_owned();
+                if emphasize {
+                    text = format!("**{}**", text);
+                }
+                let used = spans[0].content.width() + text.width();
+                spans.push(Span::raw(" ".repeat(lyric_width.saturating_sub(used))));
+                spans.push(Span::raw(text));
+                return ListItem::new(Line::from(spans));
+            }
+            match &line.words {
+                // Baris aktif dengan data per kata: kata yang sedang dinyanyikan diberi warna lain
+                Some(words) if active == Some(idx) => {
+                    let current_word = words.iter().rposition(|w| w.start <= current_pos);
+                    for (i, w) in words.iter().enumerate() {
+                        // Spasi sebelum kata tidak ikut diwarnai
+                        let word = w.text.trim_start();
+                        if i > 0 {
+                            spans.push(Span::raw(&w.text[..w.text.len() - word.len()]));
+                        }
+                        let style = if Some(i) == current_word { word_highlight } else { Style::default() };
+                        spans.push(Span::styled(word, style));
+                    }
+                }
+                // Bagian yang cocok dengan pencarian lirik diberi warna lain
+                _ => match &search_re {
+                    Some(re) => {
+                        let mut last = 0;
+                        for m in re.find_iter(&line.text) {
+                            spans.push(Span::raw(&line.text[last..m.start()]));
+                            spans.push(Span::styled(m.as_str(), search_highlight));
+                            last = m.end();
+                        }
+                        spans.push(Span::raw(&line.text[last..]));
+                    }
+                    None => spans.push(Span::raw(&line.text)),
+                },
+            }
+            if emphasize {
+                spans.insert(1, Span::raw("**"));
+                spans.push(Span::raw("**"));
+            }
+            ListItem::new(Line::from(spans))
+        }).collect();
+
+        let lyric_highlight = match &a11y {
+            Some(theme) => theme.lyric_highlight,
+            None => Style::default().bg(colors.accent).fg(Color::Black).add_modifier(Modifier::BOLD),
+        };
+        let lyrics_list = List::new(items)
+            .block(block_lyrics)
+            .highlight_style(lyric_highlight)
+            .highlight_symbol(">> ");
 
         f.render_stateful_widget(lyrics_list, meta_lyrics[1], &mut app.lyrics_state);
     }
 
-    // 4. Progress Bar
-    let current_pos = app.sink.get_pos();
-    let total_secs = app.duration.as_secs_f64();
-    let current_secs = current_pos.as_secs_f64();
+    // 4. Progress Bar
+    app.progress_area = right_chunks[1];
+    let (ratio, time) = progress(app, current_pos);
+    let mut label = format!("{}  Vol {}%", time, (app.volume * 100.0).round() as u32);
+    if app.speed != 1.0 {
+        label.push_str(&format!(" ({:.1}x)", app.speed));
+    }
+    if app.shuffle {
+        label.push_str("  [S]");
+    }
+
+    if let Some(theme) = &a11y {
+        // Isi bar dengan pola karakter supaya tidak bergantung pada beda warna
+        let block_progress = bordered(app.config.border_style).border_style(Style::default().fg(colors.border_inactive)).title(format!(" {} ", label));
+        let width = block_progress.inner(right_chunks[1]).width as usize;
+        let filled = ((width as f64) * ratio).round() as usize;
+        let bar: String = std::iter::repeat_n(theme.gauge_filled, filled)
+            .chain(std::iter::repeat_n(theme.gauge_empty, width.saturating_sub(filled)))
+            .collect();
+        f.render_widget(Paragraph::new(bar).block(block_progress), right_chunks[1]);
+    } else {
+        let gauge = Gauge::default()
+            .block(bordered(app.config.border_style).border_style(Style::default().fg(colors.border_inactive)))
+            .gauge_style(Style::default().fg(colors.progress_fill))
+            .ratio(ratio)
+            .label(label);
+        f.render_widget(gauge, right_chunks[1]);
+    }
+
+    // 5. Status Bar (prioritas: prompt perintah > pertanyaan y/n > error > info)
+    if let AppMode::Command(input) = &app.mode {
+        let completions = AppState::command_completions(input.trim());
+        let selected = completions.get(app.command_completion).filter(|_| !input.trim().is_empty());
+        // Completion terpilih ditampilkan redup di belakang ketikan
+        let hint = match selected {
+            Some(name) if name.starts_with(input.as_str()) => name[input.len()..].to_string(),
+            Some(name) => format!("  → {}", name),
+            None => String::new(),
+        };
+        let prompt = Line::from(vec![
+            Span::raw(format!(":{}", input)),
+            Span::styled(hint, Style::default().add_modifier(Modifier::DIM)),
+        ]);
+        f.render_widget(Paragraph::new(prompt), screen[1]);
+        let cursor_x = screen[1].x + 1 + input.width() as u16;
+        f.set_cursor_position((cursor_x.min(screen[1].right().saturating_sub(1)), screen[1].y));
+
+        // Popup daftar completion tepat di atas status bar
+        if !completions.is_empty() {
+            let items: Vec<ListItem> = completions.iter().map(|name| {
+                let (args, about) = COMMANDS.iter().find(|(n, _, _)| n == name).map_or(("", ""), |&(_, args, about)| (args, about));
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!(":{:<7}", name), Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(format!("{:<7} ", args)),
+                    Span::styled(about, Style::default().fg(colors.time_dim)),
+                ]))
+            }).collect();
+            let height = (items.len() as u16 + 2).min(screen[1].y);
+            let width = 64.min(screen[1].width);
+            let popup = Rect::new(screen[1].x, screen[1].y - height, width, height);
+            let mut state = ListState::default().with_selected(selected.map(|_| app.command_completion));
+            let list = List::new(items)
+                .block(bordered(app.config.border_style).title(" Commands (Tab next) "))
+                .highlight_style(Style::default().bg(colors.highlight_bg).fg(colors.highlight_fg));
+            f.render_widget(Clear, popup);
+            f.render_stateful_widget(list, popup, &mut state);
+        }
+    } else if let Some(ConfirmAction::OverwriteLrc(lrc_path)) = &app.confirm {
+        let prompt = format!("{} already exists. Overwrite? (y/n)", lrc_path.display());
+        f.render_widget(Paragraph::new(prompt).style(Style::default().fg(Color::Yellow)), screen[1]);
+    } else if let Some(err) = &app.last_error {
+        f.render_widget(Paragraph::new(err.as_str()).style(Style::default().fg(Color::Red)), screen[1]);
+    } else if let Some(msg) = &app.status {
+        f.render_widget(Paragraph::new(msg.as_str()).style(Style::default().fg(colors.metadata_secondary)), screen[1]);
+    }
+
+    // Countdown sleep timer di ujung kanan status bar
+    if let Some(deadline) = app.sleep_timer
+        && !matches!(app.mode, AppMode::Command(_))
+    {
+        let left = deadline.saturating_duration_since(Instant::now()).as_secs();
+        let countdown = Paragraph::new(format!("Sleep {:02}:{:02}", left / 60, left % 60))
+            .alignment(Alignment::Right)
+            .style(Style::default().fg(colors.accent));
+        f.render_widget(countdown, screen[1]);
+    }
+
+    // 6. Popup semua field tag (`i`), di atas layout biasa
+    if let AppMode::TagInfo(scroll) = app.mode {
+        let popup = centered_rect(70, 70, f.area());
+        let lines: Vec<Line> = if app.tag_fields.is_empty() {
+            vec![Line::from(Span::styled("No tag fields", Style::default().fg(colors.time_dim)))]
+        } else {
+            app.tag_fields.iter().map(|(key, value)| {
+                Line::from(vec![
+                    Span::styled(format!("{}: ", key), Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(value.as_str()),
+                ])
+            }).collect()
+        };
+        let block = bordered(app.config.border_style)
+            .title(" Tags (j/k scroll, i/Esc close) ")
+            .padding(Padding::horizontal(1));
+        f.render_widget(Clear, popup);
+        f.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: false }).scroll((scroll, 0)), popup);
+    }
+
+    // Overlay bantuan (`?`): tombol kiri, keterangan kanan
+    if let AppMode::Help(scroll) = app.mode {
+        let popup = centered_rect(60, 70, f.area());
+        let key_width = KEYBINDINGS.iter().map(|(_, key, _)| key.width()).max().unwrap_or(0) + 2;
+        let mut lines: Vec<Line> = vec![];
+        for section in KeybindingSection::ALL {
+            if !lines.is_empty() {
+                lines.push(Line::from(""));
+            }
+            lines.push(Line::from(Span::styled(section.label(), Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED))));
+            lines.extend(KEYBINDINGS.iter().filter(|(s, _, _)| *s == section).map(|(_, key, about)| {
+                Line::from(vec![
+                    Span::styled(format!("{}{}", key, " ".repeat(key_width - key.width())), Style::default().fg(colors.metadata_title).add_modifier(Modifier::BOLD)),
+                    Span::raw(*about),
+                ])
+            }));
+        }
+        let block = bordered(app.config.border_style)
+            .title(" Keys (↑/↓ PgUp/PgDn scroll, ?/Esc close) ")
+            .padding(Padding::horizontal(1));
+        f.render_widget(Clear, popup);
+        f.render_widget(Paragraph::new(lines).block(block).scroll((scroll, 0)), popup);
+    }
+
+    // 7. Overlay bookmark (`B`)
+    if app.mode == AppMode::Bookmarks {
+        let area = f.area();
+        let height = (app.bookmarks.len() as u16 + 2).clamp(3, area.height * 60 / 100);
+        let width = area.width * 50 / 100;
+        let popup = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+        let items: Vec<ListItem> = app.bookmarks.iter().map(|path| {
+            ListItem::new(format!("★ {}", display_file_name(path)))
+        }).collect();
+        let highlight = match &a11y {
+            Some(theme) => theme.file_highlight,
+            None => Style::default().bg(colors.highlight_bg).fg(colors.highlight_fg).add_modifier(Modifier::BOLD),
+        };
+        let list = List::new(items)
+            .block(bordered(app.config.border_style).title(" Bookmarks (Enter play, d remove) "))
+            .highlight_style(highlight)
+            .highlight_symbol("> ");
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(list, popup, &mut app.bookmarks_state);
+    }
+
+    // 8. Menu playlist virtual (`P`)
+    if app.mode == AppMode::VirtualPlaylists {
+        let (playlists, loading) = app.playlist_menu();
+        let mut items: Vec<ListItem> = playlists.iter().map(|p| ListItem::new(p.label())).collect();
+        // Baris progres tidak bisa dipilih (kursor hanya sampai item terakhir)
+        if let Some((loaded, total)) = loading {
+            let text = format!("Genres: reading tags… {}/{}", loaded, total);
+            items.push(ListItem::new(text).style(Style::default().add_modifier(Modifier::DIM)));
+        }
+        let area = f.area();
+        let height = (items.len() as u16 + 2).clamp(3, area.height * 60 / 100);
+        let width = area.width * 40 / 100;
+        let popup = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+        let highlight = match &a11y {
+            Some(theme) => theme.file_highlight,
+            None => Style::default().bg(colors.highlight_bg).fg(colors.highlight_fg).add_modifier(Modifier::BOLD),
+        };
+        let list = List::new(items)
+            .block(bordered(app.config.border_style).title(" Playlists (Enter open, Esc close) "))
+            .highlight_style(highlight)
+            .highlight_symbol("> ");
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(list, popup, &mut app.playlists_state);
+    }
+
+    // 9. Menu output audio (`D`), device yang sedang dipakai ditandai
+    if app.mode == AppMode::Devices {
+        let items: Vec<ListItem> = app.devices.iter().map(|name| {
+            let marker = if Some(name) == app.device_name.as_ref() { "● " } else { "  " };
+            ListItem::new(format!("{}{}", marker, name))
+        }).collect();
+        let area = f.area();
+        let height = (items.len() as u16 + 2).clamp(3, area.height * 60 / 100);
+        let width = area.width * 50 / 100;
+        let popup = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+        let highlight = match &a11y {
+            Some(theme) => theme.file_highlight,
+            None => Style::default().bg(colors.highlight_bg).fg(colors.highlight_fg).add_modifier(Modifier::BOLD),
+        };
+        let list = List::new(items)
+            .block(bordered(app.config.border_style).title(" Output device (Enter switch, Esc close) "))
+            .highlight_style(highlight)
+            .highlight_symbol("> ");
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(list, popup, &mut app.devices_state);
+    }
+}
+
+// Rasio progress bar & "01:23 / 04:56"
+fn progress(app: &AppState, current_pos: Duration) -> (f64, String) {
+    // Lagu CUE: progress relatif terhadap lagu virtual, bukan seluruh file
+    let (offset, total) = app.cue_span().unwrap_or((Duration::ZERO, app.duration));
+    let total_secs = total.as_secs_f64();
+    let current_secs = current_pos.saturating_sub(offset).as_secs_f64();
+    // Estimasi jam dinding bisa sedikit lewat dari durasi di akhir lagu
+    let current_secs = if total_secs > 0.0 { current_secs.min(total_secs) } else { current_secs };
     let ratio = if total_secs > 0.0 { (current_secs / total_secs).min(1.0) } else { 0.0 };
-    let label = format!("{:02}:{:02} / {:02}:{:02}", current_secs as u64/60, current_secs as u64%60, total_secs as u64/60, total_secs as u64%60);
+    let time = format!("{:02}:{:02} / {:02}:{:02}", current_secs as u64/60, current_secs as u64%60, total_secs as u64/60, total_secs as u64%60);
+    (ratio, time)
+}
+
+// Mini player: satu baris "[▶] Judul (Artis) ████45%░░░░ 02:34/05:30" untuk pane tmux yang sempit
+fn render_mini(f: &mut Frame, app: &mut AppState, colors: &Theme) {
+    const MIN_BAR_WIDTH: usize = 10;
+    let area = Rect { height: f.area().height.min(1), ..f.area() };
+    app.player_area = area;
+
+    let icon = if app.sink.empty() { "■" } else if app.sink.is_paused() { "⏸" } else { "▶" };
+    let name = if app.artist.is_empty() { app.title.clone() } else { format!("{} ({})", app.title, app.artist) };
+    let (ratio, time) = progress(app, app.position_tracker.estimate());
+    let time = format!(" {}", time.replace(" / ", "/"));
+
+    // 1. Judul dipotong supaya bar minimal MIN_BAR_WIDTH kolom
+    let width = area.width as usize;
+    let label = format!("[{}] {} ", icon, name);
+    let label = truncate_to_width(&label, width.saturating_sub(MIN_BAR_WIDTH + time.width())).to_string();
+    let bar_width = width.saturating_sub(label.width() + time.width());
 
-    let gauge = Gauge::default()
-        .block(Block::default().borders(Borders::ALL))
-        .gauge_style(Style::default().fg(Color::Magenta))
-        .ratio(ratio)
-        .label(label);
-    f.render_widget(gauge, right_chunks[1]);
+    // 2. Bar █ / ░ dengan persentase di tengah
+    let percent = format!("{}%", (ratio * 100.0).round() as u32);
+    let filled = (bar_width as f64 * ratio).round() as usize;
+    let percent_at = bar_width.saturating_sub(percent.len()) / 2;
+    let mut spans = vec![Span::styled(label.clone(), Style::default().fg(colors.metadata_title))];
+    for i in 0..bar_width {
+        let span = match i.checked_sub(percent_at).and_then(|j| percent.get(j..j + 1)) {
+            Some(c) => Span::styled(c.to_string(), Style::default().add_modifier(Modifier::BOLD)),
+            None if i < filled => Span::styled("█", Style::default().fg(colors.progress_fill)),
+            None => Span::styled("░", Style::default().fg(colors.time_dim)),
+        };
+        spans.push(span);
+    }
+    spans.push(Span::styled(time, Style::default().fg(colors.metadata_secondary)));
+    app.progress_area = Rect::new(area.x + label.width() as u16, area.y, bar_width as u16, area.height);
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
 }
 
-fn parse_lrc(content: &str) -> Vec<LyricLine> {
-    let re = Regex::new(r"\[(\d{2}):(\d{2})(?:\.(\d{2,3}))?\](.*)").unwrap();
-    let mut lines = Vec::new();
-    for line in content.lines() {
-        let line = line.trim();
-        if line.is_empty() || !re.is_match(line) { continue; }
-        if let Some(cap) = re.captures(line) {
-            let min: u64 = cap[1].parse().unwrap_or(0);
-            let sec: u64 = cap[2].parse().unwrap_or(0);
-            let millis: u64 = if let Some(m) = cap.get(3) {
-                let m_str = m.as_str();
-                match m_str.len() {
-                    1 => m_str.parse::<u64>().unwrap_or(0) * 100,
-                    2 => m_str.parse::<u64>().unwrap_or(0) * 10,
-                    _ => m_str.parse::<u64>().unwrap_or(0),
-                }
-            } else { 0 };
-            let time = Duration::from_secs(min * 60 + sec) + Duration::from_millis(millis);
-            lines.push(LyricLine { time, text: cap[4].trim().to_string() });
-        }
-    }
-    lines.sort_by_key(|k| k.time);
-    lines
+// Karaoke: baris sebelumnya, baris aktif di tengah, dua baris berikutnya; progress satu baris di bawah
+fn render_karaoke(f: &mut Frame, app: &mut AppState, colors: &Theme) {
+    let screen = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(f.area());
+    app.player_area = screen[0];
+    app.progress_area = screen[1];
+
+    let visible: Vec<&LyricLine> = app.lyrics.iter().filter(|l| app.is_lyric_visible(l)).collect();
+    let text_at = |i: Option<usize>| i.and_then(|i| visible.get(i)).map_or("", |l| l.text.as_str());
+    // Sebelum baris pertama: belum ada baris aktif, yang tampil baris yang akan datang
+    let (prev, current, next) = match app.lyrics_state.selected() {
+        Some(i) => (i.checked_sub(1), Some(i), i + 1),
+        None => (None, None, 0),
+    };
+    let mut lines = vec![Line::from(""); (screen[0].height / 2).saturating_sub(1) as usize];
+    if visible.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("No lyrics.", Style::default().fg(colors.time_dim))));
+    } else {
+        lines.push(Line::from(Span::styled(text_at(prev), Style::default().fg(colors.time_dim))));
+        lines.push(Line::from(Span::styled(
+            text_at(current),
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        )));
+        lines.push(Line::from(Span::styled(text_at(Some(next)), Style::default().fg(colors.metadata_secondary))));
+        lines.push(Line::from(Span::styled(text_at(Some(next + 1)), Style::default().fg(colors.time_dim))));
+    }
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center).wrap(Wrap { trim: true }), screen[0]);
+
+    let (ratio, time) = progress(app, app.position_tracker.estimate());
+    let strip = LineGauge::default()
+        .filled_style(Style::default().fg(colors.progress_fill))
+        .unfilled_style(Style::default().fg(colors.time_dim))
+        .label(format!("{} ", time))
+        .ratio(ratio);
+    f.render_widget(strip, screen[1]);
+}
+
+// Cover layar penuh, judul & posisi di baris paling bawah
+fn render_cover(f: &mut Frame, app: &mut AppState, colors: &Theme) {
+    let screen = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(f.area());
+    app.player_area = screen[0];
+    app.progress_area = Rect::default();
+
+    if let Some(protocol) = &mut app.cover_art {
+        let image = StatefulImage::new(None).resize(Resize::Fit(None));
+        f.render_stateful_widget(image, screen[0], protocol);
+    } else {
+        let mut lines = vec![Line::from(""); (screen[0].height / 2) as usize];
+        lines.push(Line::from(Span::styled("No cover art", Style::default().fg(colors.time_dim))));
+        f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), screen[0]);
+    }
+
+    let (_, time) = progress(app, app.position_tracker.estimate());
+    let strip = Line::from(vec![
+        Span::styled(app.title.as_str(), Style::default().add_modifier(Modifier::BOLD)),
+        Span::styled(format!("  {}", time), Style::default().fg(colors.metadata_secondary)),
+    ]);
+    f.render_widget(Paragraph::new(strip), screen[1]);
+}
+
+// `Resume queue from "Artist — Title" at 1:23 (3 more queued)? [Y/n/c]`.
+// Terminal belum menampilkan apa pun, jadi judul dibaca langsung dari tag
+fn resume_queue_question(saved: &session::Session, queued: usize) -> String {
+    let path = saved.current_path.as_deref().unwrap_or(Path::new(""));
+    let tagged_file = library::read_tagged(path);
+    let tag = tagged_file.as_ref().ok().and_then(|t| t.primary_tag());
+    let track = match (tag.and_then(|t| t.artist()), tag.and_then(|t| t.title())) {
+        (Some(artist), Some(title)) => format!("{} — {}", artist, title),
+        (None, Some(title)) => title.to_string(),
+        _ => display_file_name(path),
+    };
+    let secs = saved.position().as_secs();
+    format!("Resume queue from \"{}\" at {}:{:02} ({} more queued)? [Y/n/c]", nfc(&track), secs / 60, secs % 60, queued)
+}
+
+// Prompt sebelum loop utama. Some(true) = Y / Enter (lanjut dari posisi terakhir),
+// Some(false) = N (lagu yang sama dari awal), None = C / Esc (queue tersimpan diabaikan)
+fn prompt_resume_queue<B: Backend>(terminal: &mut Terminal<B>, question: &str) -> Result<Option<bool>> {
+    terminal.draw(|f| {
+        let width = (question.width() as u16 + 4).min(f.area().width);
+        let [row] = Layout::vertical([Constraint::Length(3)]).flex(ratatui::layout::Flex::Center).areas(f.area());
+        let [popup] = Layout::horizontal([Constraint::Length(width)]).flex(ratatui::layout::Flex::Center).areas(row);
+        f.render_widget(Paragraph::new(question).block(Block::default().borders(Borders::ALL).title(" punini ")), popup);
+    })?;
+    loop {
+        let Event::Key(key) = event::read()? else { continue };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('y' | 'Y') | KeyCode::Enter => return Ok(Some(true)),
+            KeyCode::Char('n' | 'N') => return Ok(Some(false)),
+            KeyCode::Char('c' | 'C') | KeyCode::Esc => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+// Kotak di tengah `area`, lebar & tinggi dalam persen
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(rows[1])[1]
+}
+
+fn active_border_style(colors: &Theme, a11y: &Option<AccessibilityTheme>) -> Style {
+    match a11y {
+        Some(_) => Style::default().add_modifier(Modifier::BOLD),
+        None => Style::default().fg(colors.border_active),
+    }
+}
+
+fn render_stats(f: &mut Frame, app: &AppState, colors: &Theme) {
+    let Some(stats) = &app.stats_cache else { return };
+    let label = |text: &str| Span::styled(format!("{:<18}", text), Style::default().fg(colors.time_dim));
+    let minutes = stats.total_duration.as_secs() / 60;
+    let mut lines = vec![
+        Line::from(vec![label("Tracks"), Span::raw(stats.tracks.to_string())]),
+        Line::from(vec![label("Total duration"), Span::raw(format!("{} hours {} minutes", minutes / 60, minutes % 60))]),
+        Line::from(vec![label("Average bitrate"), Span::raw(stats.avg_bitrate.map_or("—".to_string(), |b| format!("{} kbps", b)))]),
+        Line::from(vec![label("With cover art"), Span::raw(stats.with_cover.to_string())]),
+        Line::from(vec![label("With lyrics"), Span::raw(stats.with_lyrics.to_string())]),
+        Line::from(""),
+        Line::from(Span::styled("Formats", Style::default().add_modifier(Modifier::BOLD))),
+    ];
+    // Format terbanyak di atas
+    let mut formats: Vec<(&String, &usize)> = stats.formats.iter().collect();
+    formats.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    lines.extend(formats.into_iter().map(|(format, count)| Line::from(vec![label(&format!("  {}", format)), Span::raw(count.to_string())])));
+    if !stats.complete() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("Refreshing… {}/{} files read", stats.loaded, stats.tracks),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+
+    let block = bordered(app.config.border_style)
+        .title(" Library statistics (any key to close) ")
+        .padding(Padding::uniform(1));
+    f.render_widget(Paragraph::new(lines).block(block), f.area());
+}
+
+// "1 Music │ 2 Workout ▶": nomor = Alt+angka, ▶ = tab asal lagu yang sedang diputar
+fn render_tabs(f: &mut Frame, app: &AppState, area: Rect, colors: &Theme) {
+    let titles: Vec<String> = app.tabs.iter().enumerate().map(|(i, tab)| {
+        let playing = if app.playing_tab == Some(i) && !app.sink.empty() { " ▶" } else { "" };
+        format!("{} {}{}", i + 1, tab.name, playing)
+    }).collect();
+    let tabs = Tabs::new(titles)
+        .select(app.active_tab)
+        .style(Style::default().fg(colors.metadata_secondary))
+        .highlight_style(Style::default().fg(colors.highlight_fg).bg(colors.highlight_bg).add_modifier(Modifier::BOLD));
+    f.render_widget(tabs, area);
+}
+
+fn render_dir_tree(f: &mut Frame, app: &mut AppState, area: Rect, colors: &Theme, a11y: &Option<AccessibilityTheme>) {
+    let mut block = bordered(app.config.border_style).border_style(Style::default().fg(colors.border_inactive)).title(" Folders ");
+    if app.focus == Focus::Tree {
+        block = block.border_style(active_border_style(colors, a11y));
+    }
+    let items: Vec<ListItem> = app.tree.rows().into_iter().map(|row| {
+        let marker = match (row.leaf, row.expanded) {
+            (true, _) => "  ",
+            (false, true) => "▾ ",
+            (false, false) => "▸ ",
+        };
+        ListItem::new(format!("{}{}{}", "  ".repeat(row.depth), marker, row.name))
+    }).collect();
+    let highlight = match a11y {
+        Some(theme) => theme.file_highlight,
+        None => Style::default().bg(colors.highlight_bg).fg(colors.highlight_fg).add_modifier(Modifier::BOLD),
+    };
+    let list = List::new(items).block(block).highlight_style(highlight).highlight_symbol("> ");
+    f.render_stateful_widget(list, area, &mut app.tree.state);
+}
+
+fn render_file_list(f: &mut Frame, app: &mut AppState, area: Rect, colors: &Theme, a11y: &Option<AccessibilityTheme>) {
+    let source = app.virtual_playlist.as_ref().map_or("Music Folder".to_string(), |p| p.label());
+    let mut files_title = match app.sort_mode {
+        SortMode::Name => format!(" Playlist ({}) ", source),
+        mode => format!(" Playlist ({}) · {} ", source, mode.label()),
+    };
+    if app.library_flash.is_some_and(|at| at.elapsed() < LIBRARY_FLASH_TIMEOUT) {
+        files_title.push_str("· Library updated ");
+    }
+    let mut files_block = bordered(app.config.border_style).border_style(Style::default().fg(colors.border_inactive)).title(files_title);
+    // Dua panel: border panel yang menerima tombol disorot
+    if app.tree_view && app.focus == Focus::Browser {
+        files_block = files_block.border_style(active_border_style(colors, a11y));
+    }
+
+    // Saat mencari, panel dibagi: daftar hasil & kotak query di bawah
+    let (list_area, search_query) = match &app.mode {
+        AppMode::Search(query) => {
+            let parts = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(3)])
+                .split(area);
+            let search_box = Paragraph::new(format!("/{}", query))
+                .block(bordered(app.config.border_style).border_style(Style::default().fg(colors.border_inactive)).title(" Search "));
+            f.render_widget(search_box, parts[1]);
+            (parts[0], Some(query))
+        }
+        _ => (area, None),
+    };
+
+    let visible: Vec<usize> = match search_query {
+        Some(_) => app.filtered_files.clone(),
+        None => (0..app.files.len()).collect(),
+    };
+    // Karakter yang cocok dengan query diberi warna supaya kelihatan kenapa hasilnya muncul
+    let match_style = match a11y {
+        Some(_) => Style::default().add_modifier(Modifier::UNDERLINED),
+        None => Style::default().fg(Color::Yellow),
+    };
+    // Sort berdasarkan metadata: tampilkan nilainya, '?' kalau belum dimuat thread background
+    let meta = app.meta_cache.lock().ok();
+    // Lebar isi baris: tanpa border dan highlight symbol "> "
+    let name_room = list_area.width.saturating_sub(2 + 2) as usize;
+    let items: Vec<ListItem> = visible.iter().map(|&i| {
+        let name = app.file_display_name(i);
+        let matched = search_query
+            .and_then(|query| fuzzy_match(query, &name))
+            .map(|(_, positions)| positions)
+            .unwrap_or_default();
+        let path = &app.files[i];
+        let track_meta = meta.as_ref().and_then(|m| m.get(path));
+        let detail = match app.sort_mode {
+            _ if !is_audio_file(path) => None,
+            SortMode::TrackNumber => Some(track_meta.and_then(|m| m.track_number).map_or("?".to_string(), |n| n.to_string())),
+            SortMode::Duration => {
+                // Lagu CUE: panjang lagu virtual (lagu terakhir sampai akhir file)
+                let duration = match app.file_cue.get(&i).and_then(|&n| app.cue_tracks.get(path)?.get(n)) {
+                    Some(track) => track.end.or(track_meta.and_then(|m| m.duration)).map(|end| end.saturating_sub(track.start)),
+                    None => track_meta.and_then(|m| m.duration),
+                };
+                Some(duration.map_or("?".to_string(), |d| format!("{}:{:02}", d.as_secs() / 60, d.as_secs() % 60)))
+            }
+            SortMode::Rating => app.ratings.get(path).map(|&r| rating_stars(r)),
+            SortMode::Plays => Some(app.play_counts.get(path).copied().unwrap_or(0).to_string()),
+            SortMode::Name | SortMode::Modified => None,
+        };
+        let detail = detail.map(|d| format!(" [{}]", d));
+        let mut spans: Vec<Span> = vec![];
+        if app.bookmarks.contains(path) {
+            spans.push(Span::styled("★ ", Style::default().fg(Color::Yellow)));
+        }
+        // Nama yang terlalu panjang dipotong per kolom (bukan per byte) supaya [detail] tetap terlihat
+        let star_width = if app.bookmarks.contains(path) { 2 } else { 0 };
+        let name_width = name_room.saturating_sub(star_width + detail.as_ref().map_or(0, |d| d.width()));
+        let shown = if name.width() > name_width { truncate_to_width(&name, name_width.saturating_sub(1)) } else { name.as_str() };
+        spans.extend(shown.chars().enumerate().map(|(ci, c)| {
+            if matched.contains(&ci) {
+                Span::styled(c.to_string(), match_style)
+            } else {
+                Span::raw(c.to_string())
+            }
+        }));
+        if shown.len() < name.len() {
+            spans.push(Span::raw("…"));
+        }
+        if let Some(detail) = detail {
+            spans.push(Span::styled(detail, Style::default().add_modifier(Modifier::DIM)));
+        }
+        ListItem::new(Line::from(spans))
+    }).collect();
+    drop(meta);
+
+    let file_highlight = match a11y {
+        Some(theme) => theme.file_highlight,
+        None => Style::default().bg(colors.highlight_bg).fg(colors.highlight_fg).add_modifier(Modifier::BOLD),
+    };
+    let list = List::new(items)
+        .block(files_block)
+        .highlight_style(file_highlight)
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(list, list_area, &mut app.file_list_state);
+}
+
+fn render_queue(f: &mut Frame, app: &mut AppState, area: Rect, colors: &Theme, a11y: &Option<AccessibilityTheme>) {
+    let title = format!(" Queue ({}) · Tab: Browser ", app.queue.len());
+    let block = bordered(app.config.border_style).border_style(Style::default().fg(colors.border_inactive)).title(title);
+    if app.queue.is_empty() {
+        let hint = Paragraph::new("Queue is empty. Press a / A in the browser to add tracks.")
+            .block(block)
+            .style(Style::default().add_modifier(Modifier::DIM));
+        f.render_widget(hint, area);
+        return;
+    }
+
+    // Lagu yang sedang diputar bukan bagian dari queue: baris tetap di atas, tidak bisa dipilih
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let list_area = match &app.current_path {
+        Some(path) if inner.height > 1 => {
+            let [now, rest] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+            let line = format!("▶ {}", display_file_name(path));
+            f.render_widget(Paragraph::new(line).style(Style::default().fg(colors.accent).add_modifier(Modifier::BOLD)), now);
+            rest
+        }
+        _ => inner,
+    };
+    let buffered = app.queue_front_buffered();
+    let items: Vec<ListItem> = app.queue.iter().enumerate().map(|(i, path)| {
+        let item = ListItem::new(format!("{:>2}. {}", i + 1, display_file_name(path)));
+        // Sudah di-preload ke sink: tidak bisa dipindah lagi
+        if buffered && i == 0 { item.style(Style::default().add_modifier(Modifier::DIM)) } else { item }
+    }).collect();
+    let highlight = match a11y {
+        Some(theme) => theme.file_highlight,
+        None => Style::default().bg(colors.highlight_bg).fg(colors.highlight_fg).add_modifier(Modifier::BOLD),
+    };
+    let list = List::new(items)
+        .highlight_style(highlight)
+        .highlight_symbol("> ");
+    f.render_stateful_widget(list, list_area, &mut app.queue_state);
+}
+
+// Ubah salinan file lalu rename ke nama asli,
+// jadi file asli tidak rusak kalau penulisan gagal di tengah.
+fn modify_copy(path: &Path, edit: impl FnOnce(&Path) -> Result<(), String>) -> Result<(), String> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::copy(path, &tmp).map_err(|e| e.to_string())?;
+    let result = edit(&tmp).and_then(|_| fs::rename(&tmp, path).map_err(|e| e.to_string()));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+// Ubah tag utama file lewat Lofty
+fn modify_tag(path: &Path, edit: impl FnOnce(&mut Tag)) -> Result<(), String> {
+    modify_copy(path, |tmp| {
+        // Ekstensi .tmp tidak dikenal Lofty, jadi format ditebak dari isi file
+        let probe = Probe::open(tmp).map_err(|e| e.to_string())?.guess_file_type().map_err(|e| e.to_string())?;
+        let mut tagged_file = probe.read().map_err(|e| e.to_string())?;
+        let tag_type = tagged_file.primary_tag_type();
+        if tagged_file.tag(tag_type).is_none() {
+            tagged_file.insert_tag(Tag::new(tag_type));
+        }
+        edit(tagged_file.tag_mut(tag_type).ok_or("cannot create tag")?);
+        tagged_file.save_to_path(tmp, WriteOptions::default()).map_err(|e| e.to_string())
+    })
+}
+
+// Ubah tag ID3v2 MP3 frame demi frame. Frame yang tidak punya padanan di Tag generik
+// (mis. POPM) tidak bisa dihapus lewat modify_tag: Lofty menulisnya kembali
+fn modify_id3v2(path: &Path, edit: impl FnOnce(&mut Id3v2Tag)) -> Result<(), String> {
+    modify_copy(path, |tmp| {
+        let mut file = File::open(tmp).map_err(|e| e.to_string())?;
+        let mut mpeg = MpegFile::read_from(&mut file, ParseOptions::new()).map_err(|e| e.to_string())?;
+        drop(file);
+        if mpeg.id3v2().is_none() {
+            mpeg.set_id3v2(Id3v2Tag::new());
+        }
+        edit(mpeg.id3v2_mut().ok_or("cannot create ID3v2 tag")?);
+        mpeg.save_to_path(tmp, WriteOptions::default()).map_err(|e| e.to_string())
+    })
+}
+
+// Tulis satu field tag. Nilai kosong = hapus field.
+fn write_tag_field(path: &Path, field: TagField, value: &str) -> Result<(), String> {
+    let year = match field {
+        TagField::Year if !value.is_empty() => {
+            Some(value.parse::<u32>().map_err(|_| format!("invalid year '{}'", value))?)
+        }
+        _ => None,
+    };
+    modify_tag(path, |tag| match (field, value.is_empty()) {
+        (TagField::Title, true) => tag.remove_title(),
+        (TagField::Title, false) => tag.set_title(value.to_string()),
+        (TagField::Artist, true) => tag.remove_artist(),
+        (TagField::Artist, false) => tag.set_artist(value.to_string()),
+        (TagField::Album, true) => tag.remove_album(),
+        (TagField::Album, false) => tag.set_album(value.to_string()),
+        (TagField::Year, _) => match year {
+            Some(year) => tag.set_year(year),
+            None => tag.remove_year(),
+        },
+    })
+}
+
+fn rating_message(stars: u8) -> String {
+    if stars == 0 { "Rating cleared".to_string() } else { format!("Rated {}", rating_stars(stars)) }
+}
+
+// Rating ke tag file: POPM di ID3v2 MP3 (skala 0-255 ala Windows Media Player) atau
+// RATING di Vorbis comment (0-100). Format lain & file read-only dilewati (Ok(false)).
+fn write_rating_tag(path: &Path, stars: u8) -> Result<bool, String> {
+    const POPM_EMAIL: &str = "punini";
+    const POPM_VALUES: [u8; 5] = [1, 64, 128, 196, 255];
+    let file_type = FileType::from_path(path);
+    let writable = !fs::metadata(path).is_ok_and(|m| m.permissions().readonly());
+    match file_type {
+        _ if !writable => Ok(false),
+        // Hanya POPM milik punini yang diganti; rating dari player lain dibiarkan
+        Some(FileType::Mpeg) => {
+            modify_id3v2(path, |tag| {
+                tag.retain(|frame| !matches!(frame, Id3v2Frame::Popularimeter(popm) if popm.email == POPM_EMAIL));
+                if stars > 0 {
+                    let rating = POPM_VALUES[stars.min(5) as usize - 1];
+                    tag.insert(Id3v2Frame::Popularimeter(PopularimeterFrame::new(POPM_EMAIL.to_string(), rating, 0)));
+                }
+            })?;
+            Ok(true)
+        }
+        Some(t) if t.primary_tag_type() == TagType::VorbisComments => {
+            modify_tag(path, |tag| {
+                tag.remove_key(&ItemKey::Popularimeter);
+                if stars > 0 {
+                    tag.insert_text(ItemKey::Popularimeter, (stars.min(5) as u32 * 20).to_string());
+                }
+            })?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+// N lagu dengan play count tertinggi (yang belum pernah diputar tidak ikut)
+fn most_played(files: impl IntoIterator<Item = PathBuf>, play_counts: &HashMap<PathBuf, u32>, n: usize) -> Vec<PathBuf> {
+    let mut played: Vec<PathBuf> = files.into_iter().filter(|p| play_counts.get(p).is_some_and(|&c| c > 0)).collect();
+    played.sort_by_key(|p| std::cmp::Reverse(play_counts[p]));
+    played.truncate(n);
+    played
+}
+
+// Geseran pitch dari rasio tempo, dalam semitone (12 per oktaf). Rasio yang pas di
+// 2^(n/12) (mis. 2.0x = +12) ditampilkan bulat; 1.0x tidak ditampilkan.
+fn semitone_label(speed: f32) -> Option<String> {
+    if speed == 1.0 {
+        return None;
+    }
+    let semitones = 12.0 * (speed as f64).log2();
+    if (semitones - semitones.round()).abs() < 0.01 {
+        Some(format!("{:+}st", semitones.round() as i32))
+    } else {
+        Some(format!("{:+.1}st", semitones))
+    }
+}
+
+// 3 -> "★★★☆☆"
+fn rating_stars(stars: u8) -> String {
+    let stars = stars.min(5) as usize;
+    format!("{}{}", "★".repeat(stars), "☆".repeat(5 - stars))
+}
+
+// Tulis ke file .tmp dulu lalu rename, supaya file lama tidak rusak kalau gagal di tengah
+fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+// Turunkan volume sink ke 0 secara linear, lalu pause.
+// Volume dikembalikan setelah pause supaya lagu berikutnya tidak mulai dalam keadaan bisu
+fn fade_out(sink: &Arc<Sink>, steps: u32, step_ms: u64) -> JoinHandle<()> {
+    let sink = sink.clone();
+    thread::spawn(move || {
+        let start = sink.volume();
+        for i in 1..=steps {
+            sink.set_volume(start * (1.0 - i as f32 / steps as f32));
+            thread::sleep(Duration::from_millis(step_ms));
+        }
+        sink.pause();
+        sink.set_volume(start);
+    })
+}
+
+// Kebalikan fade_out: play dari volume 0 naik sampai `target` (volume pilihan user)
+fn fade_in(sink: &Arc<Sink>, target: f32, steps: u32, step_ms: u64) -> JoinHandle<()> {
+    let sink = sink.clone();
+    thread::spawn(move || {
+        sink.set_volume(0.0);
+        sink.play();
+        for i in 1..=steps {
+            thread::sleep(Duration::from_millis(step_ms));
+            sink.set_volume(target * i as f32 / steps as f32);
+        }
+    })
+}
+
+// Decode seluruh lagu ke memori untuk gapless.
+// None kalau format beda dari lagu sekarang (ganti format di tengah antrian tidak mulus)
+fn preload_track(path: &Path, format: (u16, u32)) -> Option<SamplesBuffer<i16>> {
+    let source = Decoder::new(BufReader::new(File::open(path).ok()?)).ok()?;
+    if (source.channels(), source.sample_rate()) != format {
+        debug!("not preloading {}: format differs from current track", path.display());
+        return None;
+    }
+    let samples: Vec<i16> = source.collect();
+    Some(SamplesBuffer::new(format.0, format.1, samples))
+}
+
+// Bytes file cover pertama yang ada di folder (belum di-decode)
+fn find_folder_cover(dir: &Path) -> Option<Vec<u8>> {
+    FOLDER_COVER_NAMES.iter().find_map(|name| fs::read(dir.join(name)).ok())
+}
+
+// Beberapa encoder menulis METADATA_BLOCK_PICTURE yang gagal di-parse Lofty (mis. base64 dengan
+// line break). Lofty membuang field itu, jadi comment header dibaca sendiri dari file.
+fn vorbis_block_picture(path: &Path) -> Option<Vec<u8>> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).ok()?;
+    let comments = match &magic {
+        b"fLaC" => flac_comment_block(&mut reader)?,
+        b"OggS" => {
+            let packet = ogg_comment_packet(&mut magic.as_slice().chain(reader))?;
+            // Vorbis: "\x03vorbis", Opus: "OpusTags"
+            let body = packet.strip_prefix(b"\x03vorbis").or_else(|| packet.strip_prefix(b"OpusTags"))?;
+            body.to_vec()
+        }
+        _ => return None,
+    };
+    parse_vorbis_comment_list(&comments).into_iter().find_map(|comment| {
+        let (key, value) = comment.split_once('=')?;
+        if !key.eq_ignore_ascii_case("METADATA_BLOCK_PICTURE") {
+            return None;
+        }
+        let compact: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+        let raw = BASE64_STANDARD.decode(compact).ok()?;
+        parse_flac_picture_block(&raw)
+    })
+}
+
+// Isi block VORBIS_COMMENT (tipe 4) dari header FLAC; `reader` sudah lewat magic "fLaC"
+fn flac_comment_block(reader: &mut impl Read) -> Option<Vec<u8>> {
+    loop {
+        let mut header = [0u8; 4];
+        reader.read_exact(&mut header).ok()?;
+        let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as u64;
+        if header[0] & 0x7f == 4 {
+            let mut block = vec![];
+            reader.take(len).read_to_end(&mut block).ok()?;
+            return (block.len() as u64 == len).then_some(block);
+        }
+        io::copy(&mut reader.take(len), &mut io::sink()).ok()?;
+        // Bit tertinggi: block metadata terakhir
+        if header[0] & 0x80 != 0 {
+            return None;
+        }
+    }
+}
+
+// Batas paket comment yang mau dirangkai (cover besar pun jauh di bawah ini)
+const MAX_COMMENT_PACKET: usize = 64 * 1024 * 1024;
+
+// Paket kedua stream Ogg (comment header), dirangkai dari segment page demi page.
+// Segment < 255 byte menutup paket; paket boleh menyambung ke page berikutnya.
+fn ogg_comment_packet(reader: &mut impl Read) -> Option<Vec<u8>> {
+    let mut finished = 0;
+    let mut packet = vec![];
+    loop {
+        let mut header = [0u8; 27];
+        reader.read_exact(&mut header).ok()?;
+        if &header[..4] != b"OggS" {
+            return None;
+        }
+        let mut table = vec![0u8; header[26] as usize];
+        reader.read_exact(&mut table).ok()?;
+        for len in table {
+            let mut segment = vec![0u8; len as usize];
+            reader.read_exact(&mut segment).ok()?;
+            if finished == 1 {
+                packet.extend_from_slice(&segment);
+                if packet.len() > MAX_COMMENT_PACKET {
+                    return None;
+                }
+            }
+            if len < 255 {
+                finished += 1;
+                if finished == 2 {
+                    return Some(packet);
+                }
+            }
+        }
+    }
+}
+
+// Vendor string lalu daftar "KEY=value"; semua panjang u32 little-endian.
+// Data terpotong = comment yang sudah terbaca saja
+fn parse_vorbis_comment_list(data: &[u8]) -> Vec<String> {
+    let mut pos = 0;
+    let read_chunk = |pos: &mut usize| -> Option<&[u8]> {
+        let len = u32::from_le_bytes(data.get(*pos..pos.checked_add(4)?)?.try_into().ok()?) as usize;
+        let chunk = data.get(*pos + 4..(*pos + 4).checked_add(len)?)?;
+        *pos += 4 + len;
+        Some(chunk)
+    };
+    let mut comments = vec![];
+    if read_chunk(&mut pos).is_none() {
+        return comments;
+    }
+    let Some(count) = data.get(pos..pos + 4).and_then(|b| b.try_into().ok()).map(u32::from_le_bytes) else {
+        return comments;
+    };
+    pos += 4;
+    for _ in 0..count {
+        let Some(comment) = read_chunk(&mut pos) else { break };
+        comments.push(String::from_utf8_lossy(comment).into_owned());
+    }
+    comments
+}
+
+// Format FLAC Picture block (semua angka u32 big-endian):
+// type, mime-len, mime, desc-len, desc, width, height, depth, colors, data-len, data
+fn parse_flac_picture_block(block: &[u8]) -> Option<Vec<u8>> {
+    let mut pos = 0;
+    let read_u32 = |pos: &mut usize| -> Option<usize> {
+        let bytes = block.get(*pos..pos.checked_add(4)?)?;
+        *pos += 4;
+        Some(u32::from_be_bytes(bytes.try_into().ok()?) as usize)
+    };
+
+    let _picture_type = read_u32(&mut pos)?;
+    let mime_len = read_u32(&mut pos)?;
+    pos = pos.checked_add(mime_len)?;
+    let desc_len = read_u32(&mut pos)?;
+    pos = pos.checked_add(desc_len)?;
+    // width, height, depth, color-count
+    for _ in 0..4 {
+        read_u32(&mut pos)?;
+    }
+    let data_len = read_u32(&mut pos)?;
+    block.get(pos..pos.checked_add(data_len)?).map(|data| data.to_vec())
+}
+
+// Pasangan CHAPTER001=00:00:00.000 / CHAPTER001NAME=Intro (sampai CHAPTER999)
+fn parse_vorbis_chapters(tag: &Tag) -> Vec<Chapter> {
+    if tag.tag_type() != TagType::VorbisComments {
+        return vec![];
+    }
+
+    let mut found: BTreeMap<u16, (Option<Duration>, Option<String>)> = BTreeMap::new();
+    for item in tag.items() {
+        let (ItemKey::Unknown(key), ItemValue::Text(value)) = (item.key(), item.value()) else {
+            continue;
+        };
+        let key = key.to_ascii_uppercase();
+        let Some(rest) = key.strip_prefix("CHAPTER") else { continue };
+        if rest.len() < 3 || !rest.is_char_boundary(3) {
+            continue;
+        }
+        let (num, suffix) = rest.split_at(3);
+        let Ok(num) = num.parse::<u16>() else { continue };
+        if num == 0 {
+            continue;
+        }
+        let entry = found.entry(num).or_default();
+        match suffix {
+            "" => entry.0 = parse_chapter_time(value),
+            "NAME" => entry.1 = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+
+    let mut chapters: Vec<Chapter> = found.into_iter()
+        .filter_map(|(num, (start, title))| Some(Chapter {
+            start: start?,
+            title: title.filter(|t| !t.is_empty()).unwrap_or_else(|| format!("Chapter {}", num)),
+        }))
+        .collect();
+    chapters.sort_by_key(|c| c.start);
+    chapters
+}
+
+// Format waktu chapter: HH:MM:SS.mmm
+fn parse_chapter_time(value: &str) -> Option<Duration> {
+    let mut parts = value.trim().splitn(3, ':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let mins: u64 = parts.next()?.parse().ok()?;
+    let secs: f64 = parts.next()?.parse().ok()?;
+    if mins >= 60 || !(0.0..60.0).contains(&secs) {
+        return None;
+    }
+    Some(Duration::from_secs(hours * 3600 + mins * 60) + Duration::from_secs_f64(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lofty::tag::TagItem;
+
+    // --- Waktu :goto / :seek ---
+
+    #[test]
+    fn parse_time_formats() {
+        let cases = [
+            ("83", Some(Duration::from_secs(83))),
+            ("83.5", Some(Duration::from_millis(83_500))),
+            ("1:23", Some(Duration::from_secs(83))),
+            ("1:23.25", Some(Duration::from_millis(83_250))),
+            ("1:02:03.001", Some(Duration::from_millis(3_723_001))),
+            (" 0.1234567891 ", Some(Duration::from_nanos(123_456_789))),
+            ("1:60", None),
+            ("1.5:00", None),
+            ("83.", None),
+            (".5", None),
+            ("1:2:3:4", None),
+            ("abc", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse_time(text), expected, "{:?}", text);
+        }
+    }
+
+    // --- Chapter VorbisComment ---
+
+    fn chapter_tag(items: &[(&str, &str)]) -> Tag {
+        let mut tag = Tag::new(TagType::VorbisComments);
+        for (key, value) in items {
+            tag.push_unchecked(TagItem::new(ItemKey::Unknown(key.to_string()), ItemValue::Text(value.to_string())));
+        }
+        tag
+    }
+
+    fn chapters(items: &[(&str, &str)]) -> Vec<(Duration, String)> {
+        parse_vorbis_chapters(&chapter_tag(items)).into_iter().map(|c| (c.start, c.title)).collect()
+    }
+
+    #[test]
+    fn vorbis_chapters() {
+        let paired = chapters(&[
+            ("CHAPTER002", "00:03:00.500"),
+            ("CHAPTER002NAME", "Verse"),
+            ("CHAPTER001", "00:00:00.000"),
+            ("CHAPTER001NAME", "Intro"),
+        ]);
+        assert_eq!(paired, [(Duration::ZERO, "Intro".to_string()), (Duration::from_millis(180_500), "Verse".to_string())]);
+
+        // Key huruf kecil / campur tetap dikenali; NAME yang hilang atau kosong diberi nama default
+        let mixed = chapters(&[("chapter001", "00:00:10.000"), ("Chapter002", "00:00:20.000"), ("ChApTeR002name", " ")]);
+        assert_eq!(mixed, [(Duration::from_secs(10), "Chapter 1".to_string()), (Duration::from_secs(20), "Chapter 2".to_string())]);
+
+        // NAME tanpa waktu, nomor 000, dan key lain diabaikan
+        let unpaired = chapters(&[("CHAPTER003NAME", "Orphan"), ("CHAPTER000", "00:00:01.000"), ("CHAPTERS", "x"), ("TITLE", "Song")]);
+        assert!(unpaired.is_empty(), "{:?}", unpaired);
+
+        let mut id3 = Tag::new(TagType::Id3v2);
+        id3.push_unchecked(TagItem::new(ItemKey::Unknown("CHAPTER001".to_string()), ItemValue::Text("00:00:00.000".to_string())));
+        assert!(parse_vorbis_chapters(&id3).is_empty());
+    }
+
+    #[test]
+    fn chapter_times() {
+        let cases = [
+            ("00:00:00.000", Some(Duration::ZERO)),
+            ("01:02:03.250", Some(Duration::from_millis(3_723_250))),
+            (" 00:01:05 ", Some(Duration::from_secs(65))),
+            ("00:60:00.000", None),
+            ("00:00:60.000", None),
+            ("00:00:-1.000", None),
+            ("01:02", None),
+            ("aa:00:00.000", None),
+            ("", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse_chapter_time(text), expected, "{:?}", text);
+        }
+    }
+
+    // --- Queue ---
+
+    #[test]
+    fn move_queue_entries() {
+        let moved = |from, to| {
+            let mut queue: VecDeque<char> = "abcde".chars().collect();
+            move_entry(&mut queue, from, to);
+            queue.into_iter().collect::<String>()
+        };
+        assert_eq!(moved(2, 1), "acbde");
+        assert_eq!(moved(2, 3), "abdce");
+        assert_eq!(moved(3, 0), "dabce");
+        assert_eq!(moved(1, 4), "acdeb");
+        assert_eq!(moved(2, 2), "abcde");
+    }
+
+    #[test]
+    fn resume_queue_question_without_tags() {
+        let saved = session::Session::new(Some(PathBuf::from("/nonexistent/Song.flac")), Duration::from_secs(83), 1.0);
+        assert_eq!(resume_queue_question(&saved, 3), "Resume queue from \"Song.flac\" at 1:23 (3 more queued)? [Y/n/c]");
+    }
+
+    // --- Rating di tag ---
+
+    // MPEG-1 Layer III, 128 kbps, 44.1 kHz: frame 417 byte, isi nol sudah cukup untuk Lofty
+    fn write_minimal_mp3(path: &Path) {
+        let mut frame = vec![0u8; 417];
+        frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+        fs::write(path, frame.repeat(8)).unwrap();
+    }
+
+    fn popm_frames(path: &Path) -> Vec<(String, u8)> {
+        let mpeg = MpegFile::read_from(&mut File::open(path).unwrap(), ParseOptions::new()).unwrap();
+        let Some(tag) = mpeg.id3v2() else { return vec![] };
+        tag.into_iter()
+            .filter_map(|frame| match frame {
+                Id3v2Frame::Popularimeter(popm) => Some((popm.email.clone(), popm.rating)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mp3_rating_write_overwrite_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.mp3");
+        write_minimal_mp3(&path);
+        // Rating dari player lain tidak boleh ikut tertimpa atau terhapus
+        modify_id3v2(&path, |tag| {
+            tag.insert(Id3v2Frame::Popularimeter(PopularimeterFrame::new("other@example.com".to_string(), 64, 3)));
+        })
+        .unwrap();
+        let other = ("other@example.com".to_string(), 64);
+
+        assert_eq!(write_rating_tag(&path, 4), Ok(true));
+        assert_eq!(popm_frames(&path), [other.clone(), ("punini".to_string(), 196)]);
+        assert_eq!(write_rating_tag(&path, 5), Ok(true));
+        assert_eq!(popm_frames(&path), [other.clone(), ("punini".to_string(), 255)]);
+        assert_eq!(write_rating_tag(&path, 0), Ok(true));
+        assert_eq!(popm_frames(&path), [other]);
+        assert!(!dir.path().join("track.mp3.tmp").exists());
+    }
+
+    // --- Scan folder ---
+
+    #[test]
+    fn scan_empty_dir_finds_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(scan_dir(dir.path(), 0, 5).is_empty());
+    }
+
+    // --- Sinyal ---
+
+    #[test]
+    fn sigterm_sets_shutdown_flag() {
+        let shutdown = register_shutdown_signals();
+        assert!(!shutdown.load(Ordering::Relaxed));
+        // Proses test tidak mati: handler sudah menggantikan aksi default
+        signal_hook::low_level::raise(signal_hook::consts::SIGTERM).unwrap();
+        assert!(shutdown.load(Ordering::Relaxed));
+    }
+
+    // --- Lebar kolom terminal ---
+
+    #[test]
+    fn cjk_chars_take_two_columns() {
+        let cjk = "日本語の歌詞です。歌";
+        assert_eq!(cjk.chars().count(), 10);
+        assert_eq!(cjk.width(), 20);
+    }
+
+    #[test]
+    fn truncate_to_width_never_splits_or_overflows() {
+        let cjk = "日本語の歌詞です。歌";
+        for width in 0..=22 {
+            let cut = truncate_to_width(cjk, width);
+            assert!(cut.width() <= width, "width {}: {:?}", width, cut);
+            // Karakter lebar tidak dipotong: sisa satu kolom dibiarkan kosong
+            assert_eq!(cut.width(), width.min(20) / 2 * 2, "width {}", width);
+            assert!(cjk.starts_with(cut));
+        }
+        assert_eq!(truncate_to_width("ab日c", 3), "ab");
+        assert_eq!(truncate_to_width("ab日c", 4), "ab日");
+        assert_eq!(truncate_to_width("abc", 10), "abc");
+    }
+
+    // --- M3U ---
+
+    #[test]
+    fn m3u_resolves_relative_and_absolute_paths_and_skips_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("album")).unwrap();
+        let relative = dir.path().join("album").join("01.flac");
+        let absolute = dir.path().join("02.mp3");
+        fs::write(&relative, b"").unwrap();
+        fs::write(&absolute, b"").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"").unwrap();
+
+        let playlist = format!(
+            "\u{feff}#EXTM3U\n#EXTINF:123,Artist - First\nalbum/01.flac\n\nmissing.flac\n{}\nnotes.txt\n",
+            absolute.display()
+        );
+        let (entries, skipped) = parse_m3u(&playlist, dir.path());
+        assert_eq!(entries, [(relative, Some("Artist - First".to_string())), (absolute, None)]);
+        assert_eq!(skipped, ["missing.flac", "notes.txt"]);
+    }
+
+    #[test]
+    fn m3u_extinf_name_does_not_leak_past_missing_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("b.flac"), b"").unwrap();
+        let (entries, skipped) = parse_m3u("#EXTINF:1,Gone\na.flac\nb.flac\n", dir.path());
+        assert_eq!(entries, [(dir.path().join("b.flac"), None)]);
+        assert_eq!(skipped, ["a.flac"]);
+    }
+
+    // --- METADATA_BLOCK_PICTURE ---
+
+    fn picture_block(data: &[u8]) -> Vec<u8> {
+        let mut block = vec![];
+        block.extend(3u32.to_be_bytes()); // front cover
+        block.extend(9u32.to_be_bytes());
+        block.extend(b"image/png");
+        block.extend(5u32.to_be_bytes());
+        block.extend(b"cover");
+        for value in [1u32, 1, 24, 0] {
+            block.extend(value.to_be_bytes());
+        }
+        block.extend((data.len() as u32).to_be_bytes());
+        block.extend(data);
+        block
+    }
+
+    fn comment_list(comments: &[&str]) -> Vec<u8> {
+        let mut list = vec![];
+        list.extend(6u32.to_le_bytes());
+        list.extend(b"vendor");
+        list.extend((comments.len() as u32).to_le_bytes());
+        for comment in comments {
+            list.extend((comment.len() as u32).to_le_bytes());
+            list.extend(comment.as_bytes());
+        }
+        list
+    }
+
+    // Satu page Ogg berisi paket-paket utuh (tanpa CRC yang benar; tidak dicek)
+    fn ogg_page(packets: &[&[u8]]) -> Vec<u8> {
+        let mut table = vec![];
+        for packet in packets {
+            table.extend(std::iter::repeat_n(255u8, packet.len() / 255));
+            table.push((packet.len() % 255) as u8);
+        }
+        let mut page = b"OggS".to_vec();
+        page.extend([0u8; 22]);
+        page.push(table.len() as u8);
+        page.extend(table);
+        for packet in packets {
+            page.extend(*packet);
+        }
+        page
+    }
+
+    // Base64 dengan line break: ditolak Lofty, harus tetap terbaca di sini
+    fn wrapped_base64(data: &[u8]) -> String {
+        let encoded = BASE64_STANDARD.encode(picture_block(data));
+        let lines: Vec<String> = encoded.as_bytes().chunks(20).map(|c| String::from_utf8_lossy(c).into_owned()).collect();
+        format!("METADATA_BLOCK_PICTURE={}", lines.join("\n"))
+    }
+
+    #[test]
+    fn flac_picture_block_returns_image_bytes() {
+        assert_eq!(parse_flac_picture_block(&picture_block(b"\x89PNG")), Some(b"\x89PNG".to_vec()));
+        let block = picture_block(b"\x89PNG");
+        assert_eq!(parse_flac_picture_block(&block[..block.len() - 1]), None);
+        assert_eq!(parse_flac_picture_block(&[0, 0, 0, 3, 0xff, 0xff, 0xff, 0xff]), None);
+    }
+
+    #[test]
+    fn vorbis_comment_list_stops_at_truncation() {
+        let list = comment_list(&["TITLE=a", "ARTIST=b"]);
+        assert_eq!(parse_vorbis_comment_list(&list), ["TITLE=a", "ARTIST=b"]);
+        assert_eq!(parse_vorbis_comment_list(&list[..list.len() - 1]), ["TITLE=a"]);
+        assert!(parse_vorbis_comment_list(&[]).is_empty());
+    }
+
+    #[test]
+    fn block_picture_from_raw_ogg_comment_header() {
+        let image = b"jpeg-bytes".repeat(30);
+        let comment = wrapped_base64(&image);
+        let mut packet = b"\x03vorbis".to_vec();
+        packet.extend(comment_list(&["TITLE=x", &comment]));
+        // Paket comment > 255 byte: beberapa segment, sebagian di page kedua
+        let (head, tail) = packet.split_at(255);
+        let mut file = ogg_page(&[b"\x01vorbis-ident"]);
+        let mut first = ogg_page(&[]);
+        first.truncate(26);
+        first.extend([1u8, 255]);
+        first.extend(head);
+        file.extend(first);
+        file.extend(ogg_page(&[tail]));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cover.ogg");
+        fs::write(&path, file).unwrap();
+        assert_eq!(vorbis_block_picture(&path), Some(image));
+    }
+
+    #[test]
+    fn block_picture_from_raw_flac_vorbis_comment() {
+        let comments = comment_list(&[&wrapped_base64(b"png-bytes")]);
+        let mut file = b"fLaC".to_vec();
+        file.extend([0u8, 0, 0, 34]); // STREAMINFO
+        file.extend([0u8; 34]);
+        file.push(0x80 | 4); // VORBIS_COMMENT, block terakhir
+        file.extend(&(comments.len() as u32).to_be_bytes()[1..]);
+        file.extend(comments);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cover.flac");
+        fs::write(&path, file).unwrap();
+        assert_eq!(vorbis_block_picture(&path), Some(b"png-bytes".to_vec()));
+    }
 }
@@ -0,0 +1,284 @@
+// MPRIS2 lewat D-Bus (fitur `mpris`): playerctl & applet desktop bisa mengontrol punini
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tracing::debug;
+use zbus::blocking::connection;
+use zbus::interface;
+use zbus::zvariant::{ObjectPath, OwnedValue, Value};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.punini";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+
+// State yang dibaca D-Bus; ditulis UI thread tiap tick
+#[derive(Debug, Default)]
+pub struct SharedState {
+    pub playing: bool,
+    pub stopped: bool,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub art_url: Option<String>,
+    pub length: Duration,
+    pub position: Duration,
+    pub volume: f64,
+    pub track_id: u64, // Naik setiap ganti lagu, untuk mpris:trackid
+}
+
+// Perintah dari D-Bus, diproses di run_app seperti tombol
+#[derive(Debug)]
+pub enum MprisCommand {
+    PlayPause,
+    Play,
+    Pause,
+    Next,
+    Previous,
+    Stop,
+    Seek(i64),        // Offset relatif, mikrodetik
+    SetPosition(i64), // Posisi absolut, mikrodetik
+    SetVolume(f64),
+}
+
+pub struct Mpris {
+    pub shared: Arc<Mutex<SharedState>>,
+    pub commands: Receiver<MprisCommand>,
+}
+
+// Daftarkan nama bus di thread sendiri. None kalau session bus tidak ada.
+pub fn spawn() -> Option<Mpris> {
+    let shared = Arc::new(Mutex::new(SharedState::default()));
+    let (tx, commands) = mpsc::channel();
+    let (ready_tx, ready_rx) = mpsc::channel();
+
+    let player = Player { tx: tx.clone(), shared: shared.clone() };
+    let root = Root { tx };
+    thread::Builder::new()
+        .name("punini-mpris".to_string())
+        .spawn(move || {
+            let conn = connection::Builder::session()
+                .and_then(|b| b.name(BUS_NAME))
+                .and_then(|b| b.serve_at(OBJECT_PATH, root))
+                .and_then(|b| b.serve_at(OBJECT_PATH, player))
+                .and_then(|b| b.build());
+            if let Err(e) = &conn {
+                debug!("MPRIS disabled: {}", e);
+            }
+            let ok = conn.is_ok();
+            let _ = ready_tx.send(ok);
+            if ok {
+                // Koneksi melayani request di thread internal zbus; cukup dijaga tetap hidup
+                let _conn = conn;
+                loop {
+                    thread::park();
+                }
+            }
+        })
+        .ok()?;
+
+    ready_rx.recv().ok().filter(|&ok| ok)?;
+    Some(Mpris { shared, commands })
+}
+
+struct Root {
+    tx: Sender<MprisCommand>,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    fn raise(&self) {}
+
+    fn quit(&self) {
+        // Tidak didukung (CanQuit = false); hentikan playback saja
+        let _ = self.tx.send(MprisCommand::Stop);
+    }
+
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> String {
+        "punini".to_string()
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        vec![]
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+struct Player {
+    tx: Sender<MprisCommand>,
+    shared: Arc<Mutex<SharedState>>,
+}
+
+impl Player {
+    fn send(&self, cmd: MprisCommand) {
+        let _ = self.tx.send(cmd);
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&SharedState) -> T) -> T {
+        let state = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        f(&state)
+    }
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl Player {
+    fn play_pause(&self) {
+        self.send(MprisCommand::PlayPause);
+    }
+
+    fn play(&self) {
+        self.send(MprisCommand::Play);
+    }
+
+    fn pause(&self) {
+        self.send(MprisCommand::Pause);
+    }
+
+    fn next(&self) {
+        self.send(MprisCommand::Next);
+    }
+
+    fn previous(&self) {
+        self.send(MprisCommand::Previous);
+    }
+
+    fn stop(&self) {
+        self.send(MprisCommand::Stop);
+    }
+
+    fn seek(&self, offset: i64) {
+        self.send(MprisCommand::Seek(offset));
+    }
+
+    fn set_position(&self, track_id: ObjectPath<'_>, position: i64) {
+        // Abaikan kalau trackid sudah bukan lagu sekarang (sesuai spesifikasi MPRIS)
+        if track_id.as_str() == self.read(|s| track_path(s.track_id)) {
+            self.send(MprisCommand::SetPosition(position));
+        }
+    }
+
+    #[zbus(property)]
+    fn playback_status(&self) -> String {
+        self.read(|s| match (s.stopped, s.playing) {
+            (true, _) => "Stopped",
+            (false, true) => "Playing",
+            (false, false) => "Paused",
+        })
+        .to_string()
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        self.read(|s| {
+            let mut map = HashMap::new();
+            let mut insert = |key: &str, value: Value<'_>| {
+                if let Ok(value) = value.try_to_owned() {
+                    map.insert(key.to_string(), value);
+                }
+            };
+            if let Ok(path) = ObjectPath::try_from(track_path(s.track_id)) {
+                insert("mpris:trackid", Value::from(path));
+            }
+            insert("mpris:length", Value::from(s.length.as_micros() as i64));
+            insert("xesam:title", Value::from(s.title.as_str()));
+            insert("xesam:artist", Value::from(vec![s.artist.as_str()]));
+            insert("xesam:album", Value::from(s.album.as_str()));
+            if let Some(url) = &s.art_url {
+                insert("mpris:artUrl", Value::from(url.as_str()));
+            }
+            map
+        })
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        self.read(|s| s.volume)
+    }
+
+    #[zbus(property)]
+    fn set_volume(&mut self, volume: f64) {
+        self.send(MprisCommand::SetVolume(volume));
+    }
+
+    #[zbus(property)]
+    fn position(&self) -> i64 {
+        self.read(|s| s.position.as_micros() as i64)
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn minimum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn maximum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
+
+fn track_path(id: u64) -> String {
+    format!("/org/punini/track/{}", id)
+}
+
+// Cover untuk mpris:artUrl ditulis ke file sementara (satu file per proses)
+pub fn write_art(bytes: &[u8]) -> Option<String> {
+    let path = std::env::temp_dir().join(format!("punini-{}-cover", std::process::id()));
+    std::fs::write(&path, bytes).ok()?;
+    Some(format!("file://{}", path.display()))
+}
@@ -0,0 +1,40 @@
+// Notifikasi desktop saat ganti lagu (fitur `notify`)
+use image::{DynamicImage, ImageFormat};
+use notify_rust::{Notification, NotificationHandle, Timeout};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const TIMEOUT_MS: u32 = 3000;
+const THUMB_SIZE: u32 = 128;
+
+// Tanpa display grafis (mis. lewat SSH) tidak ada yang bisa menampilkan notifikasi
+fn display_available() -> bool {
+    std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+// Thumbnail cover sebagai PNG sementara untuk icon notifikasi
+pub fn write_thumbnail(img: &DynamicImage) -> Option<PathBuf> {
+    let path = std::env::temp_dir().join(format!("punini-{}-notify.png", std::process::id()));
+    img.thumbnail(THUMB_SIZE, THUMB_SIZE).save_with_format(&path, ImageFormat::Png).ok()?;
+    Some(path)
+}
+
+// Tutup notifikasi sebelumnya supaya tidak menumpuk, lalu kirim yang baru
+pub fn show(title: &str, artist: &str, icon: Option<&Path>, previous: Option<NotificationHandle>) -> Option<NotificationHandle> {
+    if let Some(handle) = previous {
+        handle.close();
+    }
+    if !display_available() {
+        return None;
+    }
+    let mut notification = Notification::new();
+    notification
+        .appname("punini")
+        .summary(title)
+        .body(artist)
+        .timeout(Timeout::Milliseconds(TIMEOUT_MS));
+    if let Some(icon) = icon {
+        notification.icon(&icon.to_string_lossy());
+    }
+    notification.show().map_err(|e| debug!("notification failed: {}", e)).ok()
+}
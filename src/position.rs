@@ -0,0 +1,62 @@
+// Estimasi posisi playback untuk UI.
+// `Sink::get_pos` mengikuti kursor hardware dan tidak menghitung buffer internal rodio,
+// jadi setelah seek nilainya bisa mundur sesaat atau lewat dari durasi.
+// Di sini posisi dihitung dari jam dinding sejak posisi terakhir yang dipercaya,
+// dan baru disamakan lagi dengan get_pos kalau selisihnya melewati threshold.
+use std::time::{Duration, Instant};
+
+pub struct PositionTracker {
+    anchor: Duration,      // Posisi yang dipercaya...
+    anchor_at: Instant,    // ...dan kapan posisi itu dicatat
+    running: bool,         // false = pause / belum ada lagu, jam berhenti
+    settle_until: Instant, // Setelah reset, get_pos lama belum mengikuti; jangan resync dulu
+    threshold: Duration,
+    speed: f32,            // Tempo playback: 1 detik jam dinding = `speed` detik lagu
+}
+
+impl PositionTracker {
+    pub fn new(threshold: Duration) -> Self {
+        let now = Instant::now();
+        Self { anchor: Duration::ZERO, anchor_at: now, running: false, settle_until: now, threshold, speed: 1.0 }
+    }
+
+    // Posisi pasti diketahui (seek, ganti lagu)
+    pub fn reset(&mut self, pos: Duration) {
+        let now = Instant::now();
+        self.anchor = pos;
+        self.anchor_at = now;
+        self.settle_until = now + self.threshold;
+    }
+
+    // Ganti tempo: jam lanjut dari posisi sekarang dengan kecepatan baru
+    pub fn set_speed(&mut self, speed: f32) {
+        self.anchor = self.estimate();
+        self.anchor_at = Instant::now();
+        self.speed = speed;
+    }
+
+    pub fn estimate(&self) -> Duration {
+        if self.running {
+            self.anchor + self.anchor_at.elapsed().mul_f32(self.speed)
+        } else {
+            self.anchor
+        }
+    }
+
+    // Dipanggil tiap tick dengan nilai dari sink
+    pub fn sync(&mut self, reported: Duration, running: bool) {
+        // Pause/resume: bekukan atau jalankan jam dari posisi sekarang
+        if running != self.running {
+            self.anchor = self.estimate();
+            self.anchor_at = Instant::now();
+            self.running = running;
+        }
+        if Instant::now() < self.settle_until {
+            return;
+        }
+        if self.estimate().abs_diff(reported) > self.threshold {
+            self.anchor = reported;
+            self.anchor_at = Instant::now();
+        }
+    }
+}
@@ -0,0 +1,177 @@
+// Kontrol jarak jauh lewat Unix socket: satu perintah JSON per baris, dipakai `punini-ctl`
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+// Batas tunggu balasan dari UI thread sebelum koneksi dijawab error
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Perintah = padanan keybinding, mis. {"cmd":"seek","secs":30}
+#[derive(Debug, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum RemoteCommand {
+    Pause,
+    Next,
+    Prev,
+    Shuffle,
+    Repeat,
+    Seek { secs: f64 },    // Relatif, boleh negatif
+    Volume { value: f32 }, // Absolut, 0.0 - 1.5
+    Quit,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum Response {
+    Ok,
+    Error { msg: String },
+}
+
+// Satu perintah dari socket; hasilnya dikirim balik lewat `reply`
+pub struct Request {
+    pub cmd: RemoteCommand,
+    pub reply: Sender<Result<(), String>>,
+}
+
+pub struct Remote {
+    pub requests: Receiver<Request>,
+    path: PathBuf,
+}
+
+// File socket dihapus saat aplikasi keluar
+impl Drop for Remote {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// Listen di `path` dari thread sendiri. None kalau socket tidak bisa dibuat.
+pub fn spawn(path: &Path) -> Option<Remote> {
+    // Socket sisa proses yang crash dihapus, tapi jangan rebut socket instance lain
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            warn!("another punini is listening on {}, remote control disabled", path.display());
+            return None;
+        }
+        let _ = std::fs::remove_file(path);
+    }
+    let listener = match UnixListener::bind(path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("cannot listen on {}: {}", path.display(), e);
+            return None;
+        }
+    };
+
+    let (tx, requests) = mpsc::channel();
+    thread::Builder::new()
+        .name("punini-remote".to_string())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let tx = tx.clone();
+                // Satu thread per koneksi supaya client yang diam tidak memblokir yang lain
+                let spawned = thread::Builder::new()
+                    .name("punini-remote-conn".to_string())
+                    .spawn(move || serve(stream, tx));
+                if let Err(e) = spawned {
+                    warn!("cannot spawn remote connection thread: {}", e);
+                }
+            }
+        })
+        .ok()?;
+
+    debug!("remote control listening on {}", path.display());
+    Some(Remote { requests, path: path.to_path_buf() })
+}
+
+fn serve(stream: UnixStream, tx: Sender<Request>) {
+    let Ok(mut writer) = stream.try_clone() else { return };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { return };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let result = match serde_json::from_str::<RemoteCommand>(&line) {
+            Err(e) => Err(format!("invalid command: {}", e)),
+            Ok(cmd) => forward(cmd, &tx),
+        };
+        let response = match result {
+            Ok(()) => Response::Ok,
+            Err(msg) => Response::Error { msg },
+        };
+        let Ok(json) = serde_json::to_string(&response) else { return };
+        if writeln!(writer, "{}", json).is_err() {
+            return;
+        }
+    }
+}
+
+fn forward(cmd: RemoteCommand, tx: &Sender<Request>) -> Result<(), String> {
+    let (reply, reply_rx) = mpsc::channel();
+    tx.send(Request { cmd, reply }).map_err(|_| "punini is shutting down".to_string())?;
+    reply_rx.recv_timeout(REPLY_TIMEOUT).map_err(|_| "no reply from player".to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn send_line(stream: &mut UnixStream, reader: &mut BufReader<UnixStream>, line: &str) -> Value {
+        writeln!(stream, "{}", line).unwrap();
+        let mut reply = String::new();
+        reader.read_line(&mut reply).unwrap();
+        serde_json::from_str(&reply).unwrap()
+    }
+
+    #[test]
+    fn commands_round_trip_over_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("punini.sock");
+        let remote = spawn(&path).expect("socket in tempdir");
+
+        // UI thread palsu: seek dijawab ok, volume dijawab error
+        let ui = thread::spawn(move || {
+            for _ in 0..2 {
+                let request = remote.requests.recv_timeout(Duration::from_secs(5)).unwrap();
+                let result = match request.cmd {
+                    RemoteCommand::Seek { secs: 30.0 } => Ok(()),
+                    other => Err(format!("unexpected {:?}", other)),
+                };
+                request.reply.send(result).unwrap();
+            }
+            remote
+        });
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let ok = send_line(&mut stream, &mut reader, r#"{"cmd":"seek","secs":30}"#);
+        assert_eq!(ok, serde_json::json!({ "status": "ok" }));
+
+        // JSON rusak dijawab langsung, tidak sampai ke UI thread
+        let invalid = send_line(&mut stream, &mut reader, "{not json");
+        assert_eq!(invalid["status"], "error");
+        assert!(invalid["msg"].as_str().unwrap().starts_with("invalid command"));
+
+        let rejected = send_line(&mut stream, &mut reader, r#"{"cmd":"volume","value":0.5}"#);
+        assert_eq!(rejected["status"], "error");
+        assert!(rejected["msg"].as_str().unwrap().contains("Volume"));
+
+        let remote = ui.join().unwrap();
+        drop(remote);
+        assert!(!path.exists(), "socket file removed on drop");
+    }
+
+    #[test]
+    fn second_instance_does_not_steal_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("punini.sock");
+        let _first = spawn(&path).expect("socket in tempdir");
+        assert!(spawn(&path).is_none());
+    }
+}
@@ -0,0 +1,126 @@
+// Fuzzy matcher sederhana ala fzf untuk pencarian `/` di file list
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+use unicode_normalization::UnicodeNormalization;
+
+// Poin per karakter yang cocok
+const SCORE_MATCH: u32 = 1;
+// Bonus kalau cocok tepat setelah karakter sebelumnya yang juga cocok
+const BONUS_CONSECUTIVE: u32 = 5;
+// Bonus kalau cocok di awal kata ("01 - Moonlight" -> 'M')
+const BONUS_WORD_START: u32 = 3;
+
+// Skor kecocokan `needle` di `haystack` (case-insensitive), None kalau tidak cocok
+pub fn fuzzy_score(needle: &str, haystack: &str) -> Option<u32> {
+    fuzzy_match(needle, haystack).map(|(score, _)| score)
+}
+
+// Seperti fuzzy_score, plus posisi (index karakter) di haystack yang cocok
+pub fn fuzzy_match(needle: &str, haystack: &str) -> Option<(u32, Vec<usize>)> {
+    // Query yang diketik bisa saja NFD (huruf lalu aksen sebagai karakter terpisah)
+    let needle: Vec<char> = needle.nfc().flat_map(char::to_lowercase).map(base_char).collect();
+    let hay: Vec<char> = haystack.chars().collect();
+    let Some(&first) = needle.first() else { return Some((0, vec![])) };
+
+    // Coba setiap posisi awal yang mungkin, ambil hasil greedy terbaik
+    let mut best: Option<(u32, Vec<usize>)> = None;
+    for start in (0..hay.len()).filter(|&i| eq_ignore_case(hay[i], first)) {
+        let Some(candidate) = match_from(&needle, &hay, start) else {
+            // Kalau dari sini sudah tidak cocok, posisi setelahnya juga tidak
+            break;
+        };
+        if best.as_ref().is_none_or(|(score, _)| candidate.0 > *score) {
+            best = Some(candidate);
+        }
+    }
+    best
+}
+
+fn match_from(needle: &[char], hay: &[char], start: usize) -> Option<(u32, Vec<usize>)> {
+    let mut score = 0;
+    let mut positions = Vec::with_capacity(needle.len());
+    let mut hay_idx = start;
+
+    for &nc in needle {
+        let offset = hay[hay_idx..].iter().position(|&hc| eq_ignore_case(hc, nc))?;
+        let pos = hay_idx + offset;
+
+        score += SCORE_MATCH;
+        if positions.last().is_some_and(|&prev| prev + 1 == pos) {
+            score += BONUS_CONSECUTIVE;
+        }
+        if pos == 0 || !hay[pos - 1].is_alphanumeric() {
+            score += BONUS_WORD_START;
+        }
+
+        positions.push(pos);
+        hay_idx = pos + 1;
+    }
+    Some((score, positions))
+}
+
+// Aksen diabaikan: "e" cocok dengan "é" / "è". Haystack dibandingkan per karakter (sudah NFC),
+// jadi posisi hasil tetap index karakter aslinya.
+fn eq_ignore_case(hay: char, needle_lower: char) -> bool {
+    let hay = base_char(hay);
+    hay == needle_lower || hay.to_lowercase().map(base_char).eq(std::iter::once(needle_lower))
+}
+
+// Huruf dasar dari huruf beraksen ("é" -> "e"). Dekomposisi yang bukan sekadar
+// huruf + tanda aksen (mis. suku kata Hangul) dibiarkan.
+// Dipanggil per karakter haystack tiap ketikan, jadi tanpa alokasi.
+fn base_char(c: char) -> char {
+    if c.is_ascii() {
+        return c;
+    }
+    let mut base = None;
+    let mut has_marks = false;
+    let mut only_marks = true;
+    decompose_canonical(c, |part| {
+        if base.is_none() {
+            base = Some(part);
+        } else {
+            has_marks = true;
+            only_marks &= is_combining_mark(part);
+        }
+    });
+    match base {
+        Some(base) if has_marks && only_marks => base,
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accents_are_ignored() {
+        assert_eq!(fuzzy_match("e", "é"), Some((SCORE_MATCH + BONUS_WORD_START, vec![0])));
+        assert!(fuzzy_match("cafe", "Café del Mar").is_some());
+        assert!(fuzzy_match("É", "resume").is_some());
+    }
+
+    #[test]
+    fn nfd_query_matches_nfc_name() {
+        let nfd_query = "cafe\u{301}";
+        let (_, positions) = fuzzy_match(nfd_query, "Café").unwrap();
+        // Posisi = index karakter di nama asli (NFC), bukan di query
+        assert_eq!(positions, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn hangul_syllables_are_left_untouched() {
+        assert_eq!(base_char('한'), '한');
+        assert_eq!(fuzzy_match("한", "한국어").map(|(_, p)| p), Some(vec![0]));
+        // Jamo awal tidak dianggap sama dengan suku katanya
+        assert_eq!(fuzzy_match("\u{1112}", "한국어"), None);
+    }
+
+    #[test]
+    fn base_char_strips_marks_only() {
+        assert_eq!(base_char('é'), 'e');
+        assert_eq!(base_char('Ö'), 'O');
+        assert_eq!(base_char('ß'), 'ß');
+        assert_eq!(base_char('a'), 'a');
+    }
+}
@@ -0,0 +1,88 @@
+// Sesi terakhir (lagu, posisi, volume) disimpan saat keluar dengan `q`.
+// Queue disimpan terpisah sebagai queue.m3u, setiap kali diubah user
+use crate::dirs::xdg_state_dir;
+use crate::{parse_m3u, write_atomic};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+// Nama field di JSON sengaja eksplisit; field baru wajib punya default
+// supaya file sesi dari versi lama tetap bisa dibaca
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Session {
+    #[serde(rename = "track", default)]
+    pub current_path: Option<PathBuf>,
+    #[serde(rename = "position_ms", default)]
+    position_ms: u64,
+    #[serde(rename = "volume", default = "default_volume")]
+    pub volume: f32,
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+impl Session {
+    pub fn new(current_path: Option<PathBuf>, position: Duration, volume: f32) -> Self {
+        Self { current_path, position_ms: position.as_millis() as u64, volume }
+    }
+
+    pub fn position(&self) -> Duration {
+        Duration::from_millis(self.position_ms)
+    }
+}
+
+pub fn session_path() -> PathBuf {
+    xdg_state_dir().join("session.json")
+}
+
+pub fn load() -> Option<Session> {
+    let content = fs::read_to_string(session_path()).ok()?;
+    serde_json::from_str(&content)
+        .map_err(|e| warn!("ignoring unreadable session file: {}", e))
+        .ok()
+}
+
+pub fn save(session: &Session) {
+    let path = session_path();
+    let result = serde_json::to_string_pretty(session)
+        .map_err(std::io::Error::other)
+        .and_then(|json| write_atomic(&path, &json));
+    if let Err(e) = result {
+        warn!("cannot save session {}: {}", path.display(), e);
+    }
+}
+
+pub fn remove() {
+    if let Err(e) = fs::remove_file(session_path()) {
+        debug!("cannot remove session file: {}", e);
+    }
+}
+
+pub fn queue_path() -> PathBuf {
+    xdg_state_dir().join("queue.m3u")
+}
+
+// Path absolut supaya tetap valid dari folder kerja mana pun
+pub fn save_queue(queue: &VecDeque<PathBuf>) {
+    let path = queue_path();
+    let mut content = String::from("#EXTM3U\n");
+    for file in queue {
+        let abs = fs::canonicalize(file).unwrap_or_else(|_| file.clone());
+        content.push_str(&format!("{}\n", abs.display()));
+    }
+    if let Err(e) = write_atomic(&path, &content) {
+        warn!("cannot save queue {}: {}", path.display(), e);
+    }
+}
+
+// (lagu yang masih ada, path yang sudah hilang)
+pub fn load_queue() -> (VecDeque<PathBuf>, Vec<String>) {
+    let path = queue_path();
+    let Ok(content) = fs::read_to_string(&path) else { return (VecDeque::new(), vec![]) };
+    let (entries, missing) = parse_m3u(&content, path.parent().unwrap_or(&path));
+    (entries.into_iter().map(|(file, _)| file).collect(), missing)
+}
@@ -0,0 +1,67 @@
+// Urutan alami nama file: "2 - Song" sebelum "10 - Song", "1-02" (disk 1 lagu 2) sebelum "2-01"
+use std::path::Path;
+
+// (nomor di depan nama file, sisa nama huruf kecil). Tanpa nomor -> None.
+// Nomor = (angka pertama, lagu): "1-02" -> (1, 2), "10 - Song" -> (10, 0), jadi 1-02 < 2 < 10
+pub type NaturalKey = (Option<(u32, u32)>, String);
+
+pub fn natural_sort_key(path: &Path) -> NaturalKey {
+    let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    let (number, rest) = split_number(&stem);
+    let Some(number) = number else {
+        return (None, stem.to_lowercase());
+    };
+
+    // Pola disk-lagu: angka, '-', angka (tanpa spasi), mis. "1-02 Name"
+    let (track, rest) = match rest.strip_prefix('-').map(split_number) {
+        Some((Some(track), after_track)) => (track, after_track),
+        _ => (0, rest),
+    };
+
+    // Pemisah "01 - ", "01. ", "01_" tidak ikut dibandingkan
+    let rest = rest.trim_start_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '.' | '_'));
+    (Some((number, track)), rest.to_lowercase())
+}
+
+// Pisahkan digit di awal string; angka yang terlalu besar untuk u32 dianggap bukan nomor
+fn split_number(s: &str) -> (Option<u32>, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    match s[..end].parse() {
+        Ok(n) => (Some(n), &s[end..]),
+        Err(_) => (None, s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys() {
+        let cases = [
+            ("Intro.flac", (None, "intro")),
+            ("10 - Song.flac", (Some((10, 0)), "song")),
+            ("01. Song.mp3", (Some((1, 0)), "song")),
+            ("03_Song.ogg", (Some((3, 0)), "song")),
+            ("1-02 Name.flac", (Some((1, 2)), "name")),
+            ("2-01 - Name.flac", (Some((2, 1)), "name")),
+            ("100.flac", (Some((100, 0)), "")),
+            ("1 - 02 Name.flac", (Some((1, 0)), "02 name")),
+            ("1-B Side.flac", (Some((1, 0)), "b side")),
+            ("99999999999 Big.flac", (None, "99999999999 big")),
+        ];
+        for (name, (number, rest)) in cases {
+            assert_eq!(natural_sort_key(Path::new(name)), (number, rest.to_string()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn sorts_numbers_and_discs_naturally() {
+        let mut names = ["100.flac", "10 - Song.flac", "2-01 B.flac", "B.flac", "2 - Song.flac", "1-02 A.flac", "1.flac", "a.flac"];
+        names.sort_by_cached_key(|name| natural_sort_key(Path::new(name)));
+        assert_eq!(
+            names,
+            ["a.flac", "B.flac", "1.flac", "1-02 A.flac", "2 - Song.flac", "2-01 B.flac", "10 - Song.flac", "100.flac"]
+        );
+    }
+}
@@ -0,0 +1,29 @@
+// Data pengguna (bookmark, rating, ...) sebagai file JSON di $XDG_DATA_HOME/punini
+use crate::dirs::xdg_data_dir;
+use crate::write_atomic;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use tracing::warn;
+
+pub fn path(name: &str) -> PathBuf {
+    xdg_data_dir().join(name)
+}
+
+// File tidak ada = nilai default; isi rusak diabaikan (mulai dari kosong)
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    let path = path(name);
+    let Ok(content) = fs::read_to_string(&path) else { return T::default() };
+    serde_json::from_str(&content)
+        .map_err(|e| warn!("ignoring unreadable {}: {}", path.display(), e))
+        .unwrap_or_default()
+}
+
+pub fn save<T: Serialize + ?Sized>(name: &str, value: &T) -> Result<(), String> {
+    let path = path(name);
+    serde_json::to_string_pretty(value)
+        .map_err(std::io::Error::other)
+        .and_then(|json| write_atomic(&path, &json))
+        .map_err(|e| format!("cannot save {}: {}", path.display(), e))
+}
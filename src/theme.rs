@@ -0,0 +1,98 @@
+// Skema warna UI: preset bawaan, dipilih lewat [theme] di config atau --theme
+use ratatui::style::Color;
+
+// Warna per peran, bukan per widget: satu peran dipakai di beberapa panel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub progress_fill: Color,
+    pub highlight_bg: Color,
+    pub highlight_fg: Color,
+    pub border_active: Color,
+    pub border_inactive: Color,
+    pub time_dim: Color,
+    pub metadata_title: Color,
+    pub metadata_secondary: Color,
+    // Baris lirik aktif, border cover, overlay volume, countdown sleep timer
+    pub accent: Color,
+}
+
+pub const DEFAULT: Theme = Theme {
+    progress_fill: Color::Magenta,
+    highlight_bg: Color::Blue,
+    highlight_fg: Color::White,
+    border_active: Color::Blue,
+    border_inactive: Color::Reset,
+    time_dim: Color::DarkGray,
+    metadata_title: Color::Yellow,
+    metadata_secondary: Color::Gray,
+    accent: Color::Cyan,
+};
+
+// https://www.nordtheme.com/docs/colors-and-palettes
+pub const NORD: Theme = Theme {
+    progress_fill: Color::Rgb(0x81, 0xa1, 0xc1),
+    highlight_bg: Color::Rgb(0x5e, 0x81, 0xac),
+    highlight_fg: Color::Rgb(0xec, 0xef, 0xf4),
+    border_active: Color::Rgb(0x88, 0xc0, 0xd0),
+    border_inactive: Color::Rgb(0x4c, 0x56, 0x6a),
+    time_dim: Color::Rgb(0x61, 0x6e, 0x88),
+    metadata_title: Color::Rgb(0xeb, 0xcb, 0x8b),
+    metadata_secondary: Color::Rgb(0xd8, 0xde, 0xe9),
+    accent: Color::Rgb(0x88, 0xc0, 0xd0),
+};
+
+// Palet gruvbox (dark)
+pub const GRUVBOX: Theme = Theme {
+    progress_fill: Color::Rgb(0xfe, 0x80, 0x19),
+    highlight_bg: Color::Rgb(0x45, 0x85, 0x88),
+    highlight_fg: Color::Rgb(0xfb, 0xf1, 0xc7),
+    border_active: Color::Rgb(0xfa, 0xbd, 0x2f),
+    border_inactive: Color::Rgb(0x66, 0x5c, 0x54),
+    time_dim: Color::Rgb(0x92, 0x83, 0x74),
+    metadata_title: Color::Rgb(0xfa, 0xbd, 0x2f),
+    metadata_secondary: Color::Rgb(0xbd, 0xae, 0x93),
+    accent: Color::Rgb(0x8e, 0xc0, 0x7c),
+};
+
+pub const BUILTIN: &[(&str, Theme)] = &[("default", DEFAULT), ("nord", NORD), ("gruvbox", GRUVBOX)];
+
+// Nama peran yang bisa diganti di [theme], mis. progress_fill = "#ff8800"
+pub const ROLES: &[&str] = &[
+    "progress_fill",
+    "highlight_bg",
+    "highlight_fg",
+    "border_active",
+    "border_inactive",
+    "time_dim",
+    "metadata_title",
+    "metadata_secondary",
+    "accent",
+];
+
+pub fn find(name: &str) -> Option<Theme> {
+    BUILTIN.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, theme)| *theme)
+}
+
+pub fn names() -> Vec<&'static str> {
+    BUILTIN.iter().map(|(name, _)| *name).collect()
+}
+
+impl Theme {
+    // false kalau `role` tidak dikenal
+    pub fn set(&mut self, role: &str, color: Color) -> bool {
+        let slot = match role {
+            "progress_fill" => &mut self.progress_fill,
+            "highlight_bg" => &mut self.highlight_bg,
+            "highlight_fg" => &mut self.highlight_fg,
+            "border_active" => &mut self.border_active,
+            "border_inactive" => &mut self.border_inactive,
+            "time_dim" => &mut self.time_dim,
+            "metadata_title" => &mut self.metadata_title,
+            "metadata_secondary" => &mut self.metadata_secondary,
+            "accent" => &mut self.accent,
+            _ => return false,
+        };
+        *slot = color;
+        true
+    }
+}
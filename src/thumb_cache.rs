@@ -0,0 +1,69 @@
+// Thumbnail cover di disk: $XDG_CACHE_HOME/punini/thumbs/<blake3 bytes gambar>.png.
+// Cover embedded bisa beberapa MB; decode penuh cukup sekali, berikutnya baca PNG kecilnya
+use crate::dirs::xdg_cache_dir;
+use image::{DynamicImage, ImageFormat, ImageReader};
+use std::fs;
+use std::io::{self, Cursor};
+use std::path::{Path, PathBuf};
+use std::thread;
+use tracing::{debug, warn};
+
+// Sisi terpanjang thumbnail; rasio gambar tetap
+const THUMB_SIZE: u32 = 256;
+
+pub fn dir() -> PathBuf {
+    xdg_cache_dir().join("thumbs")
+}
+
+// Decode bytes gambar apa adanya (ukuran penuh), mis. untuk disimpan dengan `W`
+pub fn decode(data: &[u8]) -> Option<DynamicImage> {
+    ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .ok()
+        .and_then(|reader| reader.decode().map_err(|e| debug!("cover decode failed: {}", e)).ok())
+}
+
+// Hit: PNG dari cache. Miss: decode penuh, perkecil, lalu simpan di thread terpisah
+pub fn load(data: &[u8]) -> Option<DynamicImage> {
+    let path = dir().join(format!("{}.png", blake3::hash(data)));
+    match image::open(&path) {
+        Ok(img) => return Some(img),
+        Err(image::ImageError::IoError(e)) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => debug!("ignoring unreadable thumbnail {}: {}", path.display(), e),
+    }
+    let thumb = decode(data)?.thumbnail(THUMB_SIZE, THUMB_SIZE);
+    let to_save = thumb.clone();
+    let spawned = thread::Builder::new()
+        .name("punini-thumb".to_string())
+        .spawn(move || save(&path, &to_save));
+    if let Err(e) = spawned {
+        warn!("cannot spawn thumbnail thread: {}", e);
+    }
+    Some(thumb)
+}
+
+// Tulis ke .tmp lalu rename: instance lain tidak pernah membaca PNG yang setengah jadi
+fn save(path: &Path, img: &DynamicImage) {
+    let tmp = path.with_extension("png.tmp");
+    let result = fs::create_dir_all(dir())
+        .map_err(|e| e.to_string())
+        .and_then(|_| img.save_with_format(&tmp, ImageFormat::Png).map_err(|e| e.to_string()))
+        .and_then(|_| fs::rename(&tmp, path).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => debug!("cached thumbnail {}", path.display()),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            warn!("cannot write thumbnail {}: {}", path.display(), e);
+        }
+    }
+}
+
+// --clear-cache: hapus folder thumbs/. Folder yang memang belum ada bukan error
+pub fn clear() -> Result<PathBuf, String> {
+    let dir = dir();
+    match fs::remove_dir_all(&dir) {
+        Ok(()) => Ok(dir),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(dir),
+        Err(e) => Err(format!("cannot remove {}: {}", dir.display(), e)),
+    }
+}
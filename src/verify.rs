@@ -0,0 +1,69 @@
+// Mode --verify: cek integritas file FLAC dengan `flac --test`, tanpa TUI
+use crossterm::style::{Color, Stylize};
+use rayon::prelude::*;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Output berwarna: hanya ke terminal, dan bisa dimatikan dengan --no-color / $NO_COLOR
+pub fn color_enabled(no_color_flag: bool) -> bool {
+    !no_color_flag && std::env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal()
+}
+
+// Hasilnya false kalau ada file rusak atau `flac` tidak bisa dijalankan
+pub fn run(files: Vec<PathBuf>, color: bool) -> bool {
+    let paint = |text: &str, c: Color| if color { text.with(c).bold().to_string() } else { text.to_string() };
+
+    // `flac` tidak ada: satu pesan jelas, bukan N error yang sama
+    if let Err(e) = Command::new("flac").arg("--version").output() {
+        let hint = if e.kind() == io::ErrorKind::NotFound { " (install the flac package)" } else { "" };
+        eprintln!("{} cannot run `flac`: {}{}", paint("error:", Color::Red), e, hint);
+        return false;
+    }
+    if files.is_empty() {
+        println!("No FLAC files found");
+        return true;
+    }
+
+    // Err berisi stderr `flac` untuk file yang rusak
+    let results: Vec<(PathBuf, Result<(), String>)> = files
+        .into_par_iter()
+        .map(|path| {
+            let result = test_file(&path);
+            (path, result)
+        })
+        .collect();
+
+    let mut corrupt = 0;
+    for (path, result) in &results {
+        if let Err(output) = result {
+            corrupt += 1;
+            println!("{} {}", paint("CORRUPT", Color::Red), path.display());
+            for line in output.lines().filter(|l| !l.trim().is_empty()) {
+                println!("    {}", line.trim_end());
+            }
+        }
+    }
+    let ok = results.len() - corrupt;
+    let corrupt_label = if corrupt > 0 { paint("CORRUPT", Color::Red) } else { "CORRUPT".to_string() };
+    println!("{}: {}, {}: {}", paint("OK", Color::Green), ok, corrupt_label, corrupt);
+    corrupt == 0
+}
+
+fn test_file(path: &Path) -> Result<(), String> {
+    let output = Command::new("flac")
+        .args(["--test", "--silent", "--"])
+        .arg(path)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+}
+
+// Yang dicek hanya file .flac
+pub fn is_flac(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("flac"))
+}
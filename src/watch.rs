@@ -0,0 +1,61 @@
+// Pantau folder musik (inotify / kqueue lewat crate notify): file yang ditambah
+// atau dihapus selagi punini jalan langsung masuk / keluar dari file list
+use fs_notify::event::{AccessKind, AccessMode, ModifyKind, RenameMode};
+use fs_notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use tracing::{debug, warn};
+
+#[derive(Debug)]
+pub enum LibraryChange {
+    // File atau folder baru; juga dikirim lagi saat file selesai ditulis (metadata dibaca ulang)
+    Added(PathBuf),
+    Removed(PathBuf),
+}
+
+// Watcher berhenti (thread-nya ikut selesai) saat struct ini di-drop
+pub struct LibraryWatcher {
+    _watcher: RecommendedWatcher,
+    pub changes: Receiver<LibraryChange>,
+}
+
+// None kalau watcher tidak bisa dibuat (mis. batas inotify habis): file list tetap jalan tanpa live update
+pub fn spawn(dir: &Path) -> Option<LibraryWatcher> {
+    let (tx, changes) = mpsc::channel();
+    let handler = move |result: fs_notify::Result<Event>| {
+        let event = match result {
+            Ok(event) => event,
+            Err(e) => return debug!("watch error: {}", e),
+        };
+        let mut paths = event.paths.into_iter();
+        let changes: Vec<LibraryChange> = match event.kind {
+            EventKind::Create(_)
+            | EventKind::Access(AccessKind::Close(AccessMode::Write))
+            | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => paths.map(LibraryChange::Added).collect(),
+            EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                paths.map(LibraryChange::Removed).collect()
+            }
+            // Rename di dalam folder musik: [asal, tujuan]
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                paths.next().map(LibraryChange::Removed).into_iter().chain(paths.next().map(LibraryChange::Added)).collect()
+            }
+            _ => return,
+        };
+        for change in changes {
+            let _ = tx.send(change);
+        }
+    };
+
+    let mut watcher = match fs_notify::recommended_watcher(handler) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            warn!("cannot watch music directory: {}", e);
+            return None;
+        }
+    };
+    if let Err(e) = watcher.watch(dir, RecursiveMode::Recursive) {
+        warn!("cannot watch {}: {}", dir.display(), e);
+        return None;
+    }
+    Some(LibraryWatcher { _watcher: watcher, changes })
+}
@@ -0,0 +1,255 @@
+use crate::cache::CoverCache;
+use crate::library::read_tagged;
+use crate::lyrics::LyricsSource;
+use crate::{find_folder_cover, parse_vorbis_chapters, thumb_cache, vorbis_block_picture, write_rating_tag, Chapter};
+use image::DynamicImage;
+use lofty::config::ParseOptions;
+use lofty::file::FileType;
+use lofty::id3::v2::{ChannelType, Frame};
+use lofty::mpeg::MpegFile;
+use lofty::prelude::*;
+use lofty::tag::{ItemKey, ItemValue, Tag, TagType};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+use tracing::debug;
+
+// Permintaan dari UI thread ke worker
+pub enum WorkerMsg {
+    LoadTrack(PathBuf),
+    // Rating ke tag file; menulis ulang seluruh file, jadi jangan di UI thread
+    WriteRating { path: PathBuf, stars: u8 },
+}
+
+// Balasan worker ke UI thread
+pub enum WorkerReply {
+    MetaReady(Box<TrackMetadata>),
+    // Ok(false) = format / file yang tidak bisa diberi tag rating (dilewati)
+    RatingWritten { path: PathBuf, stars: u8, result: Result<bool, String> },
+}
+
+// Nilai ReplayGain dari tag (gain dalam dB, peak linear 0.0 - 1.0+)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReplayGain {
+    pub track_gain: Option<f32>,
+    pub track_peak: Option<f32>,
+    pub album_gain: Option<f32>,
+    pub album_peak: Option<f32>,
+}
+
+// Semua hasil kerja Lofty & file cover/lirik untuk satu lagu
+pub struct TrackMetadata {
+    pub path: PathBuf,
+    pub tags_read: bool, // false = file tidak bisa dibaca Lofty sama sekali
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub year: Option<u32>,
+    pub genre: Option<String>,
+    pub composer: Option<String>,
+    pub comment: Option<String>,
+    pub tag_fields: Vec<(String, String)>, // Semua item tag (termasuk non-standar), untuk popup `i`
+    pub duration: Option<Duration>,
+    pub bitrate: Option<u32>, // kbps
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u8>,
+    pub file_size: Option<u64>,
+    pub replaygain: ReplayGain,
+    pub cover: Option<Vec<u8>>,          // Bytes gambar (masih ter-encode)
+    pub cover_folder: Option<PathBuf>,   // Diisi kalau cover berasal dari folder.jpg dst.
+    pub cover_image: Option<DynamicImage>, // Thumbnail cover, sudah di-decode di sini
+    pub chapters: Vec<Chapter>,
+    pub lyrics: Option<LyricsSource>,    // File .lrc / .srt / .vtt diutamakan, lalu embedded
+}
+
+// Thread metadata, hidup selama aplikasi berjalan
+pub fn spawn() -> (Sender<WorkerMsg>, Receiver<WorkerReply>) {
+    let (msg_tx, msg_rx) = mpsc::channel();
+    let (reply_tx, reply_rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("punini-meta-worker".to_string())
+        .spawn(move || run(msg_rx, reply_tx))
+        .expect("failed to spawn metadata worker");
+    (msg_tx, reply_rx)
+}
+
+fn run(msg_rx: Receiver<WorkerMsg>, reply_tx: Sender<WorkerReply>) {
+    let mut covers = CoverCache::default();
+    while let Ok(msg) = msg_rx.recv() {
+        // Ambil semua yang sudah antri: lagu cukup yang terbaru, rating cukup yang terakhir per file
+        let mut load = None;
+        let mut ratings: Vec<(PathBuf, u8)> = vec![];
+        for msg in std::iter::once(msg).chain(msg_rx.try_iter()) {
+            match msg {
+                WorkerMsg::LoadTrack(path) => load = Some(path),
+                WorkerMsg::WriteRating { path, stars } => {
+                    ratings.retain(|(p, _)| *p != path);
+                    ratings.push((path, stars));
+                }
+            }
+        }
+        for (path, stars) in ratings {
+            let result = write_rating_tag(&path, stars);
+            if reply_tx.send(WorkerReply::RatingWritten { path, stars, result }).is_err() {
+                return;
+            }
+        }
+        if let Some(path) = load
+            && reply_tx.send(WorkerReply::MetaReady(Box::new(read_track(path, &mut covers)))).is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn read_track(path: PathBuf, covers: &mut CoverCache) -> TrackMetadata {
+    let mut meta = TrackMetadata {
+        path,
+        tags_read: false,
+        title: None,
+        artist: None,
+        album: None,
+        year: None,
+        genre: None,
+        composer: None,
+        comment: None,
+        tag_fields: vec![],
+        duration: None,
+        bitrate: None,
+        sample_rate: None,
+        channels: None,
+        file_size: None,
+        replaygain: ReplayGain::default(),
+        cover: None,
+        cover_folder: None,
+        cover_image: None,
+        chapters: vec![],
+        lyrics: None,
+    };
+
+    meta.file_size = fs::metadata(&meta.path).map(|m| m.len()).ok();
+
+    // 1. Tag (Lofty)
+    let mut embedded_lyrics = None;
+    match read_tagged(&meta.path) {
+        Err(e) => debug!("cannot read tags of {}: {}", meta.path.display(), e),
+        Ok(tagged_file) => {
+            meta.tags_read = true;
+            let props = tagged_file.properties();
+            meta.duration = Some(props.duration());
+            meta.bitrate = props.audio_bitrate();
+            meta.sample_rate = props.sample_rate();
+            meta.channels = props.channels();
+            if let Some(t) = tagged_file.primary_tag() {
+                meta.title = t.title().map(|s| s.to_string());
+                meta.artist = t.artist().map(|s| s.to_string());
+                meta.album = t.album().map(|s| s.to_string());
+                meta.year = t.year();
+                meta.genre = t.genre().map(|s| s.to_string());
+                meta.composer = t.get_string(&ItemKey::Composer).map(|s| s.to_string());
+                meta.comment = t.comment().map(|s| s.to_string());
+                meta.tag_fields = t.items().map(|item| (item_key_name(item.key()), item_value_text(item.value()))).collect();
+
+                // Cover Art (fallback ke METADATA_BLOCK_PICTURE mentah untuk Vorbis)
+                meta.cover = t.pictures().first().map(|pic| pic.data().to_vec()).or_else(|| {
+                    (t.tag_type() == TagType::VorbisComments).then(|| vorbis_block_picture(&meta.path)).flatten()
+                });
+
+                meta.chapters = parse_vorbis_chapters(t);
+                meta.replaygain = read_replaygain(t);
+
+                embedded_lyrics = t.items().find_map(|item| match item.value() {
+                    ItemValue::Text(text) if item.key() == &ItemKey::Lyrics => Some(text.clone()),
+                    _ => None,
+                });
+            }
+        }
+    }
+
+    // MP3 tanpa TXXX:REPLAYGAIN_*: coba frame RVA2 (tidak ikut ke tag generik Lofty)
+    if meta.replaygain.track_gain.is_none() && meta.replaygain.album_gain.is_none() {
+        read_rva2(&meta.path, &mut meta.replaygain);
+    }
+
+    // 2. Tidak ada cover embedded: cari folder.jpg / cover.png dst. di folder lagu
+    if meta.cover.is_none()
+        && let Some(dir) = meta.path.parent()
+    {
+        meta.cover = find_folder_cover(dir);
+        meta.cover_folder = meta.cover.as_ref().map(|_| dir.to_path_buf());
+    }
+
+    // Thumbnail: dari cache memori (lagu / folder terakhir), cache disk, atau decode penuh
+    let cover_key = meta.cover_folder.clone().unwrap_or_else(|| meta.path.clone());
+    meta.cover_image = meta.cover.as_deref().and_then(|data| {
+        if let Some(img) = covers.get(&cover_key) {
+            return Some(img);
+        }
+        let img = thumb_cache::load(data)?;
+        covers.insert(cover_key, img.clone());
+        Some(img)
+    });
+
+    // 3. Lyrics: file di sebelah lagu diutamakan (.lrc, .srt, lalu .vtt), lalu lirik embedded
+    let sidecar = |ext: &str| fs::read_to_string(meta.path.with_extension(ext)).ok();
+    let lyrics = sidecar("lrc")
+        .map(LyricsSource::Lrc)
+        .or_else(|| sidecar("srt").map(LyricsSource::Srt))
+        .or_else(|| sidecar("vtt").map(LyricsSource::Vtt))
+        .or(embedded_lyrics.map(LyricsSource::Lrc));
+    meta.lyrics = lyrics;
+
+    meta
+}
+
+// REPLAYGAIN_* (Vorbis comment, TXXX ID3v2, iTunes): "-6.20 dB", "0.988547"
+fn read_replaygain(tag: &Tag) -> ReplayGain {
+    let value = |key: ItemKey| {
+        let text = tag.get_string(&key)?.trim();
+        let number = text.strip_suffix("dB").or_else(|| text.strip_suffix("DB")).unwrap_or(text);
+        number.trim().parse::<f32>().ok().filter(|v| v.is_finite())
+    };
+    ReplayGain {
+        track_gain: value(ItemKey::ReplayGainTrackGain),
+        track_peak: value(ItemKey::ReplayGainTrackPeak),
+        album_gain: value(ItemKey::ReplayGainAlbumGain),
+        album_peak: value(ItemKey::ReplayGainAlbumPeak),
+    }
+}
+
+// RVA2 dengan identification "track" / "album": gain master volume dalam 1/512 dB
+fn read_rva2(path: &Path, rg: &mut ReplayGain) {
+    if FileType::from_path(path) != Some(FileType::Mpeg) {
+        return;
+    }
+    let Ok(mut file) = File::open(path) else { return };
+    let Ok(mpeg) = MpegFile::read_from(&mut file, ParseOptions::new()) else { return };
+    let Some(id3v2) = mpeg.id3v2() else { return };
+    for frame in id3v2 {
+        let Frame::RelativeVolumeAdjustment(rva2) = frame else { continue };
+        let Some(master) = rva2.channels.get(&ChannelType::MasterVolume) else { continue };
+        let gain = master.volume_adjustment as f32 / 512.0;
+        match rva2.identification.to_ascii_lowercase().as_str() {
+            "track" => rg.track_gain = Some(gain),
+            "album" => rg.album_gain = Some(gain),
+            _ => {}
+        }
+    }
+}
+
+// Nama key untuk ditampilkan: key non-standar apa adanya, sisanya nama varian ItemKey
+fn item_key_name(key: &ItemKey) -> String {
+    match key {
+        ItemKey::Unknown(name) => name.clone(),
+        other => format!("{:?}", other),
+    }
+}
+
+fn item_value_text(value: &ItemValue) -> String {
+    match value {
+        ItemValue::Text(text) | ItemValue::Locator(text) => text.clone(),
+        ItemValue::Binary(bytes) => format!("<{} bytes>", bytes.len()),
+    }
+}
@@ -0,0 +1,156 @@
+// cargo xtask man: tulis doc/punini.1 dan doc/punini-ctl.1 dari definisi clap.
+// Hasilnya ikut di-commit; CI menjalankan ulang lalu `git diff --exit-code doc/`
+use clap::{Arg, ArgAction, Command, CommandFactory};
+use clap_mangen::Man;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+// Args yang sama persis dengan binary punini, supaya man page tidak ketinggalan
+#[allow(dead_code)]
+#[path = "../../src/cli.rs"]
+mod cli;
+
+const PUNINI_DESCRIPTION: &str = "punini is a terminal music player for local files (FLAC, MP3, WAV, ...). \
+It shows cover art, synced LRC lyrics and tags, and can run without the TUI (--headless). \
+A running instance can be controlled over a Unix socket with punini-ctl(1).";
+
+const PUNINI_ENVIRONMENT: &[(&str, &str)] = &[
+    ("PUNINI_MUSIC_DIR", "Music directory when neither --dir nor a positional DIR is given."),
+    ("XDG_CONFIG_HOME", "Base directory of the config file (default ~/.config)."),
+    ("XDG_STATE_HOME", "Base directory of the session and log files (default ~/.local/state)."),
+    ("XDG_DATA_HOME", "Base directory of ratings, bookmarks and play counts (default ~/.local/share)."),
+    ("XDG_CACHE_HOME", "Base directory of the cover cache (default ~/.cache)."),
+    ("XDG_RUNTIME_DIR", "Directory of the remote control socket punini.sock."),
+    ("NO_COLOR", "Disable colors in --verify output."),
+];
+
+const PUNINI_FILES: &[(&str, &str)] = &[
+    ("~/.config/punini/config.toml", "Config file; see --print-config for every key and its current value."),
+    ("~/.local/state/punini/session.json", "Last track and position, used by --restore."),
+    ("~/.local/state/punini/queue.m3u", "Play queue; on the next start punini asks whether to resume it."),
+    ("~/.local/share/punini/ratings.json", "Track ratings (1-5 stars)."),
+    ("~/.cache/punini/thumbs/", "Cover art thumbnails; safe to delete, see --clear-cache."),
+    ("~/.local/state/punini/punini.log", "Log file, rotated daily; see --log-level."),
+];
+
+const CTL_ENVIRONMENT: &[(&str, &str)] =
+    &[("XDG_RUNTIME_DIR", "Directory of the default socket $XDG_RUNTIME_DIR/punini.sock.")];
+
+// punini-ctl parse argumennya sendiri; ini hanya deskripsi untuk man page
+fn ctl_command() -> Command {
+    let number = |name: &'static str, help: &'static str| Arg::new(name).required(true).help(help);
+    Command::new("punini-ctl")
+        .about("Send a command to a running punini")
+        .long_about(
+            "punini-ctl sends one command to a running punini(1) over its Unix socket and exits \
+             with status 0 when punini accepted it, 1 when it failed.",
+        )
+        .version(env!("CARGO_PKG_VERSION"))
+        .subcommand_required(true)
+        .disable_help_subcommand(true)
+        .subcommand_value_name("COMMAND")
+        .arg(
+            Arg::new("socket")
+                .long("socket")
+                .value_name("PATH")
+                .action(ArgAction::Set)
+                .help("Socket path (default: $XDG_RUNTIME_DIR/punini.sock, same as punini --socket)"),
+        )
+        .subcommand(Command::new("pause").about("Toggle play / pause"))
+        .subcommand(Command::new("next").about("Play the next track"))
+        .subcommand(Command::new("prev").about("Play the previous track"))
+        .subcommand(Command::new("shuffle").about("Toggle shuffle"))
+        .subcommand(Command::new("repeat").about("Cycle the repeat mode"))
+        .subcommand(Command::new("quit").about("Quit punini"))
+        .subcommand(Command::new("seek").about("Seek to a position").arg(number("secs", "Position in seconds")))
+        .subcommand(Command::new("volume").about("Set the volume").arg(number("value", "Volume, 0.0 - 1.5")))
+}
+
+fn roff_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('-', "\\-")
+}
+
+// Section tambahan yang tidak dibuat clap_mangen: daftar .TP (judul tebal + penjelasan)
+fn render_list(out: &mut dyn io::Write, title: &str, items: &[(&str, &str)]) -> io::Result<()> {
+    writeln!(out, ".SH {}", title)?;
+    for (name, about) in items {
+        writeln!(out, ".TP\n\\fB{}\\fR\n{}", roff_escape(name), roff_escape(about))?;
+    }
+    Ok(())
+}
+
+fn render_see_also(out: &mut dyn io::Write, pages: &[&str]) -> io::Result<()> {
+    let pages: Vec<String> = pages.iter().map(|page| format!("\\fB{}\\fR(1)", roff_escape(page))).collect();
+    writeln!(out, ".SH \"SEE ALSO\"\n{}", pages.join(", "))
+}
+
+fn render_page(
+    cmd: Command,
+    environment: &[(&str, &str)],
+    files: &[(&str, &str)],
+    see_also: &[&str],
+) -> io::Result<Vec<u8>> {
+    let man = Man::new(cmd.clone());
+    let mut out = Vec::new();
+    man.render_title(&mut out)?;
+    man.render_name_section(&mut out)?;
+    man.render_synopsis_section(&mut out)?;
+    man.render_description_section(&mut out)?;
+    man.render_options_section(&mut out)?;
+    // Bukan render_subcommands_section: itu merujuk ke man page per subcommand yang tidak ada
+    let commands: Vec<(String, String)> = cmd
+        .get_subcommands()
+        .map(|sub| {
+            let args: Vec<String> = sub.get_positionals().map(|arg| format!("<{}>", arg.get_id())).collect();
+            let about = sub.get_about().map(|about| about.to_string()).unwrap_or_default();
+            (format!("{} {}", sub.get_name(), args.join(" ")).trim_end().to_string(), about)
+        })
+        .collect();
+    if !commands.is_empty() {
+        let commands: Vec<(&str, &str)> = commands.iter().map(|(name, about)| (name.as_str(), about.as_str())).collect();
+        render_list(&mut out, "COMMANDS", &commands)?;
+    }
+    render_list(&mut out, "ENVIRONMENT", environment)?;
+    if !files.is_empty() {
+        render_list(&mut out, "FILES", files)?;
+    }
+    render_see_also(&mut out, see_also)?;
+    man.render_version_section(&mut out)?;
+    Ok(out)
+}
+
+fn generate_man(doc_dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(doc_dir)?;
+    let punini = cli::Args::command().long_about(PUNINI_DESCRIPTION);
+    let pages = [
+        ("punini.1", render_page(punini, PUNINI_ENVIRONMENT, PUNINI_FILES, &["punini-ctl"])?),
+        ("punini-ctl.1", render_page(ctl_command(), CTL_ENVIRONMENT, &[], &["punini"])?),
+    ];
+    for (name, page) in pages {
+        let path = doc_dir.join(name);
+        fs::write(&path, page)?;
+        println!("wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn main() {
+    let task = std::env::args().nth(1);
+    // Selalu relatif ke root repo, dari folder mana pun `cargo xtask` dijalankan
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap_or(Path::new("."));
+    let doc_dir = root.join("doc");
+    match task.as_deref() {
+        Some("man") => {
+            if let Err(e) = generate_man(&doc_dir) {
+                eprintln!("xtask man: {}", e);
+                process::exit(1);
+            }
+        }
+        _ => {
+            eprintln!("usage: cargo xtask man");
+            process::exit(2);
+        }
+    }
+}